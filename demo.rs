@@ -1,9 +1,46 @@
+use std::path::Path;
+
 use super::src::engine::input;
 use super::src::engine::timer::Timer;
 use super::src::engine::window::Window;
+use super::src::foreign::{cache, gltf_export, load_model};
+use super::src::renderer::model::Model;
 use super::src::scene::viewer::World;
 use super::src::screen_capture::system::ScreenCapture;
 
+const SCENE_FILE: &str = "world.json";
+
+/// command line tools that run instead of the demo, None if none were asked for  
+/// `--convert <model> <out.dhm>` writes a model out as an engine model cache  
+/// `--export <model> <out.gltf|out.glb>` writes a model out as glTF  
+/// they still open a window since textures get read back through its gl context
+pub fn run_tool(args: &[String]) -> Option<Result<(), String>> {
+    let tool = args.get(1)?.as_str();
+    if tool != "--convert" && tool != "--export" {
+        return None;
+    }
+
+    let [_, _, input, output] = args else {
+        return Some(Err(format!("usage: {tool} <model> <out>")));
+    };
+    let (input, output) = (Path::new(input), Path::new(output));
+
+    let _window = Window::create(String::from("rust engine"), 800, 600);
+    let result = if tool == "--convert" {
+        cache::convert(input, output).map_err(|e| e.to_string())
+    } else {
+        let mut model = Model::default();
+        load_model(input, &mut model)
+            .map_err(|e| format!("{}: {e}", input.display()))
+            .and_then(|()| gltf_export::export(&model, output))
+    };
+
+    if result.is_ok() {
+        eprintln!("wrote {}", output.display());
+    }
+    Some(result)
+}
+
 pub struct Demo {
     window: Window,
     world: World,
@@ -15,7 +52,10 @@ pub struct Demo {
 impl Demo {
     pub fn new() -> Self {
         let window = Window::create(String::from("rust engine"), 800, 600);
-        let world = World::new();
+        let world = World::from_file(Path::new(SCENE_FILE)).unwrap_or_else(|err| {
+            eprintln!("failed to load scene: {err}");
+            std::process::exit(1);
+        });
         let timer = Timer::new();
 
         let (w, h) = window.get_size();
@@ -68,6 +108,18 @@ impl Demo {
                 self.running = false;
             }
 
+            // F5 writes any changes made to the scene back to its file
+            if let sdl2::event::Event::KeyDown {
+                keycode: Some(sdl2::keyboard::Keycode::F5),
+                ..
+            } = event
+            {
+                match self.world.save(Path::new(SCENE_FILE)) {
+                    Ok(()) => eprintln!("\nscene saved to {SCENE_FILE}"),
+                    Err(err) => eprintln!("\nfailed to save scene: {err}"),
+                }
+            }
+
             input::mouse_input(&event, &mut self.world.camera);
         }
     }
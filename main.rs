@@ -4,6 +4,16 @@ mod demo;
 
 fn main() {
     use demo::Demo;
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(result) = demo::run_tool(&args) {
+        if let Err(err) = result {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let mut app = Demo::new();
     app.run();
 }
@@ -6,6 +6,7 @@ layout(location = 2) in vec2 tc;
 layout(location = 3) in vec3 col;
 layout(location = 4) in vec4 weights;
 layout(location = 5) in ivec4 boneIds;
+layout(location = 6) in vec2 tc1;
 
 uniform mat4 transform;
 uniform mat4 view;
@@ -17,7 +18,9 @@ out vs_Out {
     vec3 fragCol;
     vec3 fragPos;
     vec2 texCoords;
+    vec2 texCoords1;
     vec4 lightSpace;
+    vec3 localPos;
 } vs_out;
 
 const int MAX_BONES = 300;
@@ -26,10 +29,14 @@ uniform mat4 boneMats[MAX_BONES];
 
 void main() {
 
-    mat4 skin = boneMats[boneIds[0]] * weights[0];
-    skin += boneMats[boneIds[1]] * weights[1];
-    skin += boneMats[boneIds[2]] * weights[2];
-    skin += boneMats[boneIds[3]] * weights[3];
+    // meshes without a skin have no weights, leave them where they are
+    mat4 skin = mat4(1.0);
+    if(weights[0] + weights[1] + weights[2] + weights[3] > 0.0) {
+        skin = boneMats[boneIds[0]] * weights[0];
+        skin += boneMats[boneIds[1]] * weights[1];
+        skin += boneMats[boneIds[2]] * weights[2];
+        skin += boneMats[boneIds[3]] * weights[3];
+    }
 
     mat4 final_mat = transform * skin;
     gl_Position = projection * view * final_mat * vec4(pos, 1.0);
@@ -37,6 +44,9 @@ void main() {
     vs_out.normal = mat3(transpose(inverse(final_mat))) * norm;
     vs_out.fragCol = col;
     vs_out.texCoords = tc;
+    vs_out.texCoords1 = tc1;
+    // skinned but not yet placed in the world so patterns move along with the mesh
+    vs_out.localPos = vec3(skin * vec4(pos, 1.0));
 
     vs_out.fragPos = vec3(transform * vec4(pos, 1.0));
    // vs_out.lightSpace=lightSpace;
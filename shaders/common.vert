@@ -4,6 +4,7 @@ layout(location = 0) in vec3 pos;
 layout(location = 1) in vec3 norm;
 layout(location = 2) in vec2 tc;
 layout(location = 3) in vec3 col;
+layout(location = 6) in vec2 tc1;
 //layout(location = 3) in vec4 boneWeights;
 //layout(location = 4) in ivec4 boneIds;
 
@@ -17,7 +18,9 @@ out vs_Out {
     vec3 fragCol;
     vec3 fragPos;
     vec2 texCoords;
+    vec2 texCoords1;
     vec4 lightSpace;
+    vec3 localPos;
 } vs_out;
 
 void main() {
@@ -25,8 +28,10 @@ void main() {
     vec4 worldPos = transform * vec4(pos, 1.0);
 
     vs_out.texCoords = tc;
+    vs_out.texCoords1 = tc1;
     vs_out.fragCol = col;
     vs_out.fragPos = vec3(worldPos);
+    vs_out.localPos = pos;
     vs_out.normal = mat3(transpose(inverse(transform))) * norm;
     vs_out.lightSpace = lightSpace * vec4(vs_out.fragPos, 1.0);
 
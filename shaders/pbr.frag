@@ -6,28 +6,79 @@ in vs_Out {
     vec3 fragCol;
     vec3 fragPos;
     vec2 texCoords;
+    vec2 texCoords1;
     vec4 lightSpace;
+    vec3 localPos;
 } fs_in;
 
-#define MAX_LIGHTS 20
-uniform struct Light {
+// point lights, same layout as the phong shader so both get the same uniforms
+#define MAX_POINT_LIGHTS 20 // MAX_POINT_LIGHTS in lights.rs
+uniform struct pointLight {
     vec3 color;
     vec3 position;
-} lights[MAX_LIGHTS];
-uniform int lightCount;
+} pointLights[MAX_POINT_LIGHTS];
+uniform int pointLightCount;
 
-uniform vec3 camPos;
+// cos of the cone angles so the shader doesn't have to take any
+#define MAX_SPOT_LIGHTS 8 // MAX_SPOT_LIGHTS in lights.rs
+uniform struct spotLight {
+    vec3 color;
+    vec3 position;
+    vec3 direction;
+    float innerCos;
+    float outerCos;
+} spotLights[MAX_SPOT_LIGHTS];
+uniform int spotLightCount;
+
+// directional light(sun)
+uniform vec3 L_direction;
+uniform vec3 L_color;
+
+uniform vec3 viewPos;
 
 /*** material defination ***/
-uniform vec3 baseColor; // or emissive factor
+// every texture gets multiplied with its factor, like glTF does
+uniform vec3 baseColor;
+uniform float alpha;
 uniform float metallicFactor;
 uniform float roughness;
-uniform float ao;
-
-uniform sampler2D baseTexture;
-uniform sampler2D metallicTexture;
-uniform bool hasBaseTexture; // diffuse map
-uniform bool hasMetallicTexture; // specular map
+uniform float ao; // ambient strength
+uniform vec3 emissive;
+uniform float normalScale;
+uniform float occlusionStrength;
+
+uniform sampler2D baseTexture; // srgb
+uniform sampler2D metallicTexture; // g: roughness, b: metalness
+uniform sampler2D normalTexture; // tangent space
+uniform sampler2D occlusionTexture; // r: occlusion
+uniform sampler2D emissiveTexture; // srgb
+uniform bool hasBaseTexture;
+uniform bool hasMetallicTexture;
+uniform bool hasNormalTexture;
+uniform bool hasOcclusionTexture;
+uniform bool hasEmissiveTexture;
+
+// uv set each texture reads, 0 or 1
+uniform int baseUv;
+uniform int metallicUv;
+uniform int normalUv;
+uniform int occlusionUv;
+uniform int emissiveUv;
+vec2 uv(int);
+
+// 0: opaque, 1: mask, 2: blend
+uniform int alphaMode;
+uniform float alphaCutoff;
+uniform bool doubleSided;
+
+// procedural surface pattern
+// 0: none, 1: checkered, 2: striped
+uniform int patternType;
+uniform bool patternObjectSpace;
+uniform vec3 patternColor;
+uniform float patternScale;
+uniform float patternWidth;
+vec3 apply_pattern(vec3);
 
 out vec4 color;
 
@@ -35,6 +86,8 @@ float distributionGGX(vec3, vec3, float);
 float GeometrySchlickGGX(float, float);
 float geometrySmith(vec3, vec3, vec3, float);
 vec3 frenselSchlick(float, vec3);
+vec3 perturb_normal(vec3);
+vec3 brdf(vec3, vec3, vec3, vec3, vec3, float, float, vec3);
 
 const float PI = 3.14159265359;
 
@@ -44,44 +97,78 @@ float blend(float far);
 
 //_________________________________________________________________________
 void main() {
-    vec3 N = normalize(fs_in.normal);
-    vec3 V = normalize(camPos);
+    vec4 base = vec4(baseColor * fs_in.fragCol, alpha);
+    if(hasBaseTexture) {
+        vec4 tex = texture(baseTexture, uv(baseUv));
+        base *= vec4(pow(tex.rgb, vec3(2.2)), tex.a);
+    }
 
-    vec3 f0 = vec3(0.04);
-    f0 = mix(f0, baseColor, metallicFactor);
+    if(alphaMode == 1 && base.a < alphaCutoff)
+        discard;
+    if(alphaMode == 0)
+        base.a = 1.0;
 
-    vec3 lo = vec3(0.0);
-    for(int i = 0; i < lightCount; i++) {
-        vec3 L = normalize(lights[i].position - fs_in.fragPos);
-        vec3 H = normalize(V + L);
+    vec3 albedo = apply_pattern(base.rgb);
 
-        float distance = length(lights[i].position - fs_in.fragPos);
-        float attenuation = 1.0 / pow(distance, 2.0);
-        vec3 radiance = lights[i].color * attenuation;
+    float metallic = metallicFactor;
+    float rough = roughness;
+    if(hasMetallicTexture) {
+        vec4 mr = texture(metallicTexture, uv(metallicUv));
+        rough *= mr.g;
+        metallic *= mr.b;
+    }
+    rough = clamp(rough, 0.04, 1.0);
 
-        float NDF = distributionGGX(N, H, roughness);
-        float G = geometrySmith(N, V, L, roughness);
-        vec3 F = frenselSchlick(clamp(dot(H, V), 0.0, 1.0), f0);
+    float occlusion = 1.0;
+    if(hasOcclusionTexture) {
+        float sampled = texture(occlusionTexture, uv(occlusionUv)).r;
+        occlusion = 1.0 + occlusionStrength * (sampled - 1.0);
+    }
 
-        vec3 numerator = NDF * G * F;
+    vec3 N = normalize(fs_in.normal);
+    if(doubleSided && !gl_FrontFacing)
+        N = -N;
+    if(hasNormalTexture)
+        N = perturb_normal(N);
 
-        float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0) + 0.0001;
-        vec3 specular = numerator / denominator;
+    vec3 V = normalize(viewPos - fs_in.fragPos);
 
-        vec3 KS = F;
+    vec3 f0 = vec3(0.04);
+    f0 = mix(f0, albedo, metallic);
 
-        vec3 KD = vec3(1.0) - KS;
+    // sun
+    vec3 lo = brdf(N, V, normalize(-L_direction), L_color, albedo, metallic, rough, f0);
 
-        KD *= 1.0 - metallicFactor;
+    for(int i = 0; i < pointLightCount; i++) {
+        vec3 L = normalize(pointLights[i].position - fs_in.fragPos);
 
-        float NdotL = max(dot(N, L), 0.0);
+        float distance = length(pointLights[i].position - fs_in.fragPos);
+        float attenuation = 1.0 / pow(distance, 2.0);
+        vec3 radiance = pointLights[i].color * attenuation;
 
-        lo += (KD * baseColor / PI + specular) + radiance * NdotL;
+        lo += brdf(N, V, L, radiance, albedo, metallic, rough, f0);
     }
 
-    vec3 ambient = vec3(0.03) * baseColor * ao;
+    for(int i = 0; i < spotLightCount; i++) {
+        vec3 L = normalize(spotLights[i].position - fs_in.fragPos);
+
+        float distance = length(spotLights[i].position - fs_in.fragPos);
+        float attenuation = 1.0 / pow(distance, 2.0);
+
+        float cosTheta = dot(-L, spotLights[i].direction);
+        float cone = smoothstep(spotLights[i].outerCos, spotLights[i].innerCos, cosTheta);
+        vec3 radiance = spotLights[i].color * attenuation * cone;
+
+        lo += brdf(N, V, L, radiance, albedo, metallic, rough, f0);
+    }
 
-    vec3 result = ambient + lo;
+    vec3 ambient = vec3(ao) * albedo * occlusion;
+
+    vec3 emission = emissive;
+    if(hasEmissiveTexture)
+        emission *= pow(texture(emissiveTexture, uv(emissiveUv)).rgb, vec3(2.2));
+
+    vec3 result = ambient + lo + emission;
 
     // HDR tonemapping
     // result = result / (result + vec3(1.0));
@@ -89,7 +176,7 @@ void main() {
     // gamma correction
     result = pow(result, vec3(1.0 / 2.2));
 
-    color = vec4(result, 1.0);
+    color = vec4(result, base.a);
 }
 
 //*** function deinations **//
@@ -130,7 +217,73 @@ vec3 frenselSchlick(float cosTheta, vec3 f0) {
     return f0 + (1.0 - f0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
 }
 //_________________________________________________________________________
+vec3 apply_pattern(vec3 col) {
+    if(patternType == 0)
+        return col;
+
+    vec3 p = patternObjectSpace ? fs_in.localPos : vec3(fs_in.texCoords, 0.0);
+    p *= patternScale;
+
+    float mask = 0.0;
+    if(patternType == 1) {
+        vec3 cell = floor(p);
+        float sum = patternObjectSpace ? cell.x + cell.y + cell.z : cell.x + cell.y;
+        mask = mod(sum, 2.0);
+    } else if(patternType == 2) {
+        mask = step(fract(p.x), patternWidth);
+    }
+
+    return mix(col, patternColor, mask);
+}
+//_________________________________________________________________________
+// outgoing light for a single light, L points towards the light
+vec3 brdf(vec3 N, vec3 V, vec3 L, vec3 radiance, vec3 albedo, float metallic, float rough, vec3 f0) {
+    vec3 H = normalize(V + L);
+
+    float NDF = distributionGGX(N, H, rough);
+    float G = geometrySmith(N, V, L, rough);
+    vec3 F = frenselSchlick(clamp(dot(H, V), 0.0, 1.0), f0);
+
+    vec3 numerator = NDF * G * F;
+    float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0) + 0.0001;
+    vec3 specular = numerator / denominator;
+
+    vec3 KS = F;
+    vec3 KD = vec3(1.0) - KS;
+    KD *= 1.0 - metallic;
+
+    float NdotL = max(dot(N, L), 0.0);
+
+    return (KD * albedo / PI + specular) * radiance * NdotL;
+}
+//_________________________________________________________________________
+// no tangents in the vertex data, build the tangent frame from screen space derivatives
+vec3 perturb_normal(vec3 N) {
+    vec2 tc = uv(normalUv);
+    vec3 mapped = texture(normalTexture, tc).xyz * 2.0 - 1.0;
+    mapped.xy *= normalScale;
+
+    vec3 dp1 = dFdx(fs_in.fragPos);
+    vec3 dp2 = dFdy(fs_in.fragPos);
+    vec2 duv1 = dFdx(tc);
+    vec2 duv2 = dFdy(tc);
+
+    vec3 dp2perp = cross(dp2, N);
+    vec3 dp1perp = cross(N, dp1);
+    vec3 T = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 B = dp2perp * duv1.y + dp1perp * duv2.y;
+
+    float invmax = inversesqrt(max(max(dot(T, T), dot(B, B)), 1e-12));
+    mat3 TBN = mat3(T * invmax, B * invmax, N);
+
+    return normalize(TBN * mapped);
+}
+//_________________________________________________________________________
+vec2 uv(int set) {
+    return set == 1 ? fs_in.texCoords1 : fs_in.texCoords;
+}
+//_________________________________________________________________________
 float blend(float far) {
-    float distance = clamp(length(fs_in.fragPos - camPos), 0.0, far);
+    float distance = clamp(length(fs_in.fragPos - viewPos), 0.0, far);
     return (pow(distance / far, 2.0));
 }
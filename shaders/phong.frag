@@ -9,11 +9,14 @@ in vs_Out {
     vec3 fragCol;
     vec3 fragPos;
     vec2 texCoords;
+    vec2 texCoords1;
     vec4 lightSpace;
+    vec3 localPos;
 } fs_in;
 uniform vec3 viewPos;
 
 // point light data and calculations
+// spot lights are pbr only, phong materials don't read them
 #define MAX_POINT_LIGHTS 20
 uniform struct pointLight {
     vec3 color;
@@ -36,6 +39,16 @@ uniform bool hasSpecularTex;
 uniform sampler2D diffuse_tex;
 uniform sampler2D specular_tex;
 uniform sampler2D albedo;
+float specular_map();
+
+// procedural surface pattern
+// 0: none, 1: checkered, 2: striped
+uniform int patternType;
+uniform bool patternObjectSpace;
+uniform vec3 patternColor;
+uniform float patternScale;
+uniform float patternWidth;
+vec3 apply_pattern(vec3);
 
 // blending with background based on distance from camera
 // also can be used to create a lazy fog effect
@@ -62,6 +75,8 @@ void main() {
 
     }
 
+    col = apply_pattern(col);
+
     col = pow(col, vec3(1.0 / 2.2));
 
     result += directional_light(col);
@@ -99,7 +114,7 @@ vec3 calc_pointlight(pointLight light, vec3 col) {
 
     vec3 viewDir = normalize(viewPos - fs_in.fragPos);
     vec3 halfwaydir = normalize(lightDir + viewDir);
-    float spec = pow(max(dot(norm, halfwaydir), 0.0), specular_strength);
+    float spec = pow(max(dot(norm, halfwaydir), 0.0), specular_strength) * specular_map();
     vec3 specular = spec * light.color;
     result += specular;
 
@@ -109,6 +124,30 @@ vec3 calc_pointlight(pointLight light, vec3 col) {
     return result;
 }
 
+// how shiny the surface is at this point, the red channel of the specular map
+float specular_map() {
+    return hasSpecularTex ? texture(specular_tex, fs_in.texCoords).r : 1.0;
+}
+
+vec3 apply_pattern(vec3 col) {
+    if(patternType == 0)
+        return col;
+
+    vec3 p = patternObjectSpace ? fs_in.localPos : vec3(fs_in.texCoords, 0.0);
+    p *= patternScale;
+
+    float mask = 0.0;
+    if(patternType == 1) {
+        vec3 cell = floor(p);
+        float sum = patternObjectSpace ? cell.x + cell.y + cell.z : cell.x + cell.y;
+        mask = mod(sum, 2.0);
+    } else if(patternType == 2) {
+        mask = step(fract(p.x), patternWidth);
+    }
+
+    return mix(col, patternColor, mask);
+}
+
 float ortho_shadow() {
 
     vec3 proojCoords = fs_in.lightSpace.xyz / fs_in.lightSpace.w;
@@ -139,7 +178,7 @@ vec3 directional_light(vec3 col) {
 
     vec3 viewDir = normalize(viewPos - fs_in.fragPos);
     vec3 halfwaydir = normalize(lightDir + viewDir);
-    float spec = pow(max(dot(norm, halfwaydir), 0.0), 32.0);
+    float spec = pow(max(dot(norm, halfwaydir), 0.0), specular_strength) * specular_map();
     vec3 specular = spec * L_color * col;
 
     if(shadowsEnabled)
@@ -0,0 +1,182 @@
+// _______________________________________________________________________________________________________
+// _______________________________________________________________________________________________________
+// additive animation, clips stored as offsets from a reference pose
+// so they can be layered over whatever else is playing(breathing, recoil, nods...)
+
+use crate::src::animation::clip::Clip;
+use crate::src::animation::pose::Pose;
+use crate::src::math::quaternion::Quat;
+
+/// the pose a clip starts with, on top of rest for joints it doesn't animate
+pub fn first_frame(clip: &Clip, rest: &Pose) -> Pose {
+    let mut clip = clip.clone();
+    let mut pose = rest.clone();
+    let start = clip.get_start_time();
+    clip.sample(&mut pose, start);
+    pose
+}
+
+/// turn a clip into offsets from the reference pose
+/// sampled over an identity pose the result gives what Pose::add expects
+pub fn make_additive(clip: &Clip, reference: &Pose) -> Clip {
+    let mut additive = clip.clone();
+
+    for track in additive.tracks.iter_mut() {
+        let Some(base) = reference.joints.get(track.id as usize) else {
+            continue;
+        };
+
+        // translations are offsets, tangents stay as they are
+        let translation = base.translation.to_array();
+        for frame in track.position.frames.iter_mut() {
+            for (value, base) in frame.m_value.iter_mut().zip(translation) {
+                *value -= base;
+            }
+        }
+
+        // rotations are relative to the base, tangents get rotated along
+        let inverse = base.orientation.inverse();
+        let relative = |value: &mut [f32; 4]| {
+            *value = (inverse * Quat::from(value)).to_array();
+        };
+        for frame in track.rotation.frames.iter_mut() {
+            relative(&mut frame.m_value);
+            relative(&mut frame.m_in);
+            relative(&mut frame.m_out);
+        }
+
+        // scales are ratios
+        let scaling = base.scaling.to_array();
+        let ratio = |value: &mut [f32; 3]| {
+            for (value, base) in value.iter_mut().zip(scaling) {
+                if base != 0.0 {
+                    *value /= base;
+                }
+            }
+        };
+        for frame in track.scaling.frames.iter_mut() {
+            ratio(&mut frame.m_value);
+            ratio(&mut frame.m_in);
+            ratio(&mut frame.m_out);
+        }
+    }
+
+    additive
+}
+
+/// additive clip relative to its own first frame, the usual way to author them
+pub fn make_additive_from_first_frame(clip: &Clip, rest: &Pose) -> Clip {
+    make_additive(clip, &first_frame(clip, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::src::animation::curves::Interpolation;
+    use crate::src::animation::frame::Frame;
+    use crate::src::animation::track_transform::TransformTrack;
+    use crate::src::math::transform::Transform;
+    use crate::src::math::vec3::Vec3;
+
+    /// one second clip on joint 1 moving x 1 to 3, turning 30 to 90 degrees around y and scaling 2 to 4
+    fn clip() -> Clip {
+        let mut track = TransformTrack::new();
+        track.id = 1;
+        track.position.interpolation = Interpolation::Linear;
+        track.rotation.interpolation = Interpolation::Linear;
+        track.scaling.interpolation = Interpolation::Linear;
+        for (time, x, angle, scale) in [(0.0, 1.0, 30.0, 2.0), (1.0, 3.0, 90.0, 4.0)] {
+            let mut frame = Frame::new();
+            frame.time = time;
+            frame.m_value = [x, 0.0, 0.0];
+            track.position.frames.push(frame);
+
+            let mut frame = Frame::new();
+            frame.time = time;
+            frame.m_value = Quat::create(angle, Vec3::new(0.0, 1.0, 0.0)).to_array();
+            track.rotation.frames.push(frame);
+
+            let mut frame = Frame::new();
+            frame.time = time;
+            frame.m_value = [scale; 3];
+            track.scaling.frames.push(frame);
+        }
+
+        let mut clip = Clip::new();
+        clip.tracks.push(track);
+        clip.re_calculate_duration();
+        clip
+    }
+
+    fn identity() -> Pose {
+        let mut pose = Pose::new();
+        pose.resize(2);
+        pose
+    }
+
+    fn sample(clip: &Clip, base: &Pose, time: f32) -> Pose {
+        let mut clip = clip.clone();
+        let mut pose = base.clone();
+        clip.sample(&mut pose, time);
+        pose
+    }
+
+    fn assert_close(a: &Transform, b: &Transform) {
+        let a_orientation = a.orientation.to_array();
+        let b_orientation = b.orientation.to_array();
+        let values = [
+            (
+                a.translation.to_array().to_vec(),
+                b.translation.to_array().to_vec(),
+            ),
+            (a_orientation.to_vec(), b_orientation.to_vec()),
+            (a.scaling.to_array().to_vec(), b.scaling.to_array().to_vec()),
+        ];
+        for (a, b) in values {
+            for (a, b) in a.iter().zip(b) {
+                assert!((a - b).abs() < 1e-4, "{a} != {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn first_frame_is_the_clip_start_over_rest() {
+        let mut rest = identity();
+        rest.joints[0].translation = Vec3::new(0.0, 5.0, 0.0);
+
+        let first = first_frame(&clip(), &rest);
+        assert_close(&first.joints[0], &rest.joints[0]);
+        assert_close(&first.joints[1], &sample(&clip(), &rest, 0.0).joints[1]);
+    }
+
+    #[test]
+    fn additive_on_its_reference_is_identity() {
+        let additive = make_additive_from_first_frame(&clip(), &identity());
+
+        let start = sample(&additive, &identity(), 0.0);
+        assert_close(&start.joints[1], &Transform::DEFAULT);
+
+        // halfway the offsets are 1 along x, 30 degrees around y and 1.5 times the scale
+        let half = sample(&additive, &identity(), 0.5);
+        let mut expected = Transform::DEFAULT;
+        expected.translation = Vec3::new(1.0, 0.0, 0.0);
+        expected.scaling = Vec3::new(1.5, 1.5, 1.5);
+        expected.orientation = Quat::create(30.0, Vec3::new(0.0, 1.0, 0.0));
+        assert_close(&half.joints[1], &expected);
+    }
+
+    #[test]
+    fn additive_added_to_its_reference_gives_the_clip_back() {
+        let reference = first_frame(&clip(), &identity());
+        let additive = make_additive(&clip(), &reference);
+
+        for time in [0.0, 0.25, 0.5, 0.75] {
+            let mut pose = reference.clone();
+            pose.add(&sample(&additive, &identity(), time), 1.0);
+            assert_close(
+                &pose.joints[1],
+                &sample(&clip(), &identity(), time).joints[1],
+            );
+        }
+    }
+}
@@ -0,0 +1,401 @@
+// _______________________________________________________________________________________________________
+// _______________________________________________________________________________________________________
+// blend spaces, clips placed at parameter values(speed, velocity...) and mixed by where the parameters land
+// clips play phase synced, they all share one normalised time so foot contacts line up
+// the 2d weights are freeform directional, gradient band interpolation in polar space from
+// "rune skovbo johansen - automated semi-procedural animation for character locomotion"
+
+use crate::src::animation::clip::Clip;
+use crate::src::animation::pose::Pose;
+
+/// how far around the circle counts compared to how far out, same value unity uses
+const ANGLE_WEIGHT: f32 = 2.0;
+
+/// a clip placed in a blend space
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlendPoint<P> {
+    pub position: P,
+    /// index into the models animations
+    pub clip: usize,
+    /// shifts the clips cycle(0 to 1) for clips that don't start on the same foot
+    pub offset: f32,
+}
+
+/// a clips share of the blended pose
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClipWeight {
+    pub clip: usize,
+    pub offset: f32,
+    pub weight: f32,
+}
+
+/// clips on a line, e.g. walk at 1.5 and run at 4 by speed
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlendSpace1D {
+    /// float parameter picking the position
+    pub parameter: String,
+    /// kept sorted by position
+    points: Vec<BlendPoint<f32>>,
+}
+
+impl BlendSpace1D {
+    pub fn new(parameter: &str) -> Self {
+        Self {
+            parameter: parameter.to_string(),
+            points: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, position: f32, clip: usize, offset: f32) {
+        let i = self.points.partition_point(|p| p.position <= position);
+        self.points.insert(
+            i,
+            BlendPoint {
+                position,
+                clip,
+                offset,
+            },
+        );
+    }
+
+    pub fn points(&self) -> &[BlendPoint<f32>] {
+        &self.points
+    }
+
+    /// the two clips either side of value, values past the ends play the end clip
+    pub fn weights(&self, value: f32) -> Vec<ClipWeight> {
+        let weight = |p: &BlendPoint<f32>, weight| ClipWeight {
+            clip: p.clip,
+            offset: p.offset,
+            weight,
+        };
+
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return Vec::new();
+        };
+        if value <= first.position {
+            return vec![weight(first, 1.0)];
+        }
+        if value >= last.position {
+            return vec![weight(last, 1.0)];
+        }
+
+        let i = self.points.partition_point(|p| p.position <= value);
+        let (a, b) = (&self.points[i - 1], &self.points[i]);
+        let t = (value - a.position) / (b.position - a.position);
+
+        [weight(a, 1.0 - t), weight(b, t)]
+            .into_iter()
+            .filter(|w| w.weight > 0.0)
+            .collect()
+    }
+}
+
+/// clips on a plane, e.g. by velocity x/z with idle in the middle and walks/runs around it
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlendSpace2D {
+    /// float parameters for the two axes
+    pub x: String,
+    pub y: String,
+    pub points: Vec<BlendPoint<[f32; 2]>>,
+}
+
+impl BlendSpace2D {
+    pub fn new(x: &str, y: &str) -> Self {
+        Self {
+            x: x.to_string(),
+            y: y.to_string(),
+            points: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, position: [f32; 2], clip: usize, offset: f32) {
+        self.points.push(BlendPoint {
+            position,
+            clip,
+            offset,
+        });
+    }
+
+    /// freeform directional weights, every clip's influence is the smallest it gets
+    /// against any other clip along the direction/magnitude between them
+    pub fn weights(&self, value: [f32; 2]) -> Vec<ClipWeight> {
+        let mut influences: Vec<f32> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                self.points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, b)| Self::influence(a.position, b.position, value))
+                    .fold(1.0, f32::min)
+                    .max(0.0)
+            })
+            .collect();
+
+        // outside of every band(or points on top of each other) the closest clip takes it
+        if influences.iter().sum::<f32>() <= 0.0 {
+            let distance = |p: &[f32; 2]| (p[0] - value[0]).hypot(p[1] - value[1]);
+            let closest =
+                self.points.iter().enumerate().min_by(|(_, a), (_, b)| {
+                    distance(&a.position).total_cmp(&distance(&b.position))
+                });
+            if let Some((i, _)) = closest {
+                influences[i] = 1.0;
+            }
+        }
+
+        let total: f32 = influences.iter().sum();
+        self.points
+            .iter()
+            .zip(influences)
+            .filter(|(_, h)| *h > 0.0)
+            .map(|(p, h)| ClipWeight {
+                clip: p.clip,
+                offset: p.offset,
+                weight: h / total,
+            })
+            .collect()
+    }
+
+    /// influence of a on value with b as the only other clip,
+    /// 1 at a and 0 once value gets as far as b
+    fn influence(a: [f32; 2], b: [f32; 2], value: [f32; 2]) -> f32 {
+        let len = |v: [f32; 2]| v[0].hypot(v[1]);
+        let (la, lb, lv) = (len(a), len(b), len(value));
+
+        let mean = (la + lb) * 0.5;
+        if mean <= 0.0 {
+            return 0.0;
+        }
+
+        // magnitude difference relative to the pair, angle difference around the origin
+        let ab = [(lb - la) / mean, Self::angle(a, b) * ANGLE_WEIGHT];
+        let av = [(lv - la) / mean, Self::angle(a, value) * ANGLE_WEIGHT];
+
+        let ab_len2 = ab[0] * ab[0] + ab[1] * ab[1];
+        if ab_len2 <= 0.0 {
+            return 0.0;
+        }
+        1.0 - (av[0] * ab[0] + av[1] * ab[1]) / ab_len2
+    }
+
+    /// signed angle from a to b, 0 if either sits on the origin
+    fn angle(a: [f32; 2], b: [f32; 2]) -> f32 {
+        if a == [0.0; 2] || b == [0.0; 2] {
+            return 0.0;
+        }
+        let cross = a[0] * b[1] - a[1] * b[0];
+        let dot = a[0] * b[0] + a[1] * b[1];
+        cross.atan2(dot)
+    }
+}
+
+//_______________________________________________________________________________________________
+// phase synced playback
+
+/// length of one cycle of the blend, the clip durations mixed by weight
+pub fn synced_duration(weights: &[ClipWeight], clips: &[Clip]) -> f32 {
+    weights
+        .iter()
+        .filter_map(|w| clips.get(w.clip).map(|c| c.get_duration() * w.weight))
+        .sum()
+}
+
+/// where in a clip the shared phase(cycles played, the fraction is how far through) lands
+pub fn clip_time(weight: &ClipWeight, clip: &Clip, phase: f32) -> f32 {
+    clip.get_start_time() + (phase + weight.offset).rem_euclid(1.0) * clip.get_duration()
+}
+
+/// sample every clip at the same point of its cycle and blend them by weight on top of the rest pose
+pub fn sample_synced(
+    weights: &[ClipWeight],
+    clips: &mut [Clip],
+    rest: &Pose,
+    phase: f32,
+    out: &mut Pose,
+) {
+    out.clone_from(rest);
+
+    let mut sample = rest.clone();
+    let mut total = 0.0;
+    for weight in weights.iter() {
+        let Some(clip) = clips.get_mut(weight.clip) else {
+            continue;
+        };
+        let time = clip_time(weight, clip, phase);
+
+        sample.clone_from(rest);
+        clip.sample(&mut sample, time);
+
+        // running average, each clip gets its share of what's been blended so far
+        total += weight.weight;
+        if total > 0.0 {
+            out.blend(&sample, weight.weight / total);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::src::animation::clip::tests::slide as clip;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    fn weight_of(weights: &[ClipWeight], clip: usize) -> f32 {
+        weights
+            .iter()
+            .filter(|w| w.clip == clip)
+            .map(|w| w.weight)
+            .sum()
+    }
+
+    /// idle in the middle, a clip each way around it
+    fn directions() -> BlendSpace2D {
+        let mut space = BlendSpace2D::new("x", "z");
+        space.add([0.0, 0.0], 0, 0.0);
+        space.add([0.0, 1.5], 1, 0.0);
+        space.add([-1.5, 0.0], 2, 0.0);
+        space.add([1.5, 0.0], 3, 0.0);
+        space.add([0.0, -1.5], 4, 0.0);
+        space
+    }
+
+    #[test]
+    fn one_d_clamps_at_both_ends() {
+        let mut space = BlendSpace1D::new("speed");
+        space.add(4.0, 1, 0.0);
+        space.add(1.5, 0, 0.0);
+
+        for value in [-1.0, 0.0, 1.5] {
+            let weights = space.weights(value);
+            assert_eq!(weights.len(), 1);
+            assert_eq!(weight_of(&weights, 0), 1.0);
+        }
+        for value in [4.0, 10.0] {
+            let weights = space.weights(value);
+            assert_eq!(weights.len(), 1);
+            assert_eq!(weight_of(&weights, 1), 1.0);
+        }
+    }
+
+    #[test]
+    fn one_d_splits_between_neighbours() {
+        let mut space = BlendSpace1D::new("speed");
+        space.add(0.0, 0, 0.0);
+        space.add(1.5, 1, 0.0);
+        space.add(4.0, 2, 0.0);
+
+        let weights = space.weights(2.75);
+        assert_eq!(weights.len(), 2);
+        assert!(close(weight_of(&weights, 1), 0.5));
+        assert!(close(weight_of(&weights, 2), 0.5));
+
+        let weights = space.weights(0.5);
+        assert!(close(weight_of(&weights, 0), 2.0 / 3.0));
+        assert!(close(weight_of(&weights, 1), 1.0 / 3.0));
+    }
+
+    #[test]
+    fn two_d_weights_sum_to_one() {
+        let space = directions();
+        for value in [
+            [0.3, 0.6],
+            [-1.0, 1.0],
+            [1.2, -0.4],
+            [3.0, 3.0],
+            [0.0, -0.2],
+        ] {
+            let weights = space.weights(value);
+            let total: f32 = weights.iter().map(|w| w.weight).sum();
+            assert!(close(total, 1.0), "{value:?} sums to {total}");
+            assert!(weights.iter().all(|w| w.weight > 0.0));
+        }
+    }
+
+    #[test]
+    fn two_d_point_plays_only_its_clip() {
+        let space = directions();
+        for point in space.points.iter() {
+            let weights = space.weights(point.position);
+            assert_eq!(weights.len(), 1, "{:?}", point.position);
+            assert_eq!(weights[0].clip, point.clip);
+            assert!(close(weights[0].weight, 1.0));
+        }
+    }
+
+    #[test]
+    fn two_d_origin_is_idle() {
+        let weights = directions().weights([0.0, 0.0]);
+        assert_eq!(weights.len(), 1);
+        assert_eq!(weights[0].clip, 0);
+
+        // halfway out mixes idle with the direction only
+        let weights = directions().weights([0.0, 0.75]);
+        assert!(close(weight_of(&weights, 0), 0.5));
+        assert!(close(weight_of(&weights, 1), 0.5));
+    }
+
+    #[test]
+    fn synced_clips_share_their_phase() {
+        let clips = [clip(1.0, 1.0), clip(2.0, 2.0)];
+        let weights = [
+            ClipWeight {
+                clip: 0,
+                offset: 0.0,
+                weight: 0.5,
+            },
+            ClipWeight {
+                clip: 1,
+                offset: 0.0,
+                weight: 0.5,
+            },
+        ];
+        assert!(close(synced_duration(&weights, &clips), 1.5));
+
+        for phase in [0.0, 0.25, 0.7, 3.4] {
+            let a = clip_time(&weights[0], &clips[0], phase) / clips[0].get_duration();
+            let b = clip_time(&weights[1], &clips[1], phase) / clips[1].get_duration();
+            assert!(close(a, b), "phase {phase}: {a} vs {b}");
+            assert!(close(a, phase.fract()));
+        }
+
+        // offsets shift a clips cycle
+        let shifted = ClipWeight {
+            offset: 0.5,
+            ..weights[1]
+        };
+        assert!(close(clip_time(&shifted, &clips[1], 0.75), 0.5));
+    }
+
+    #[test]
+    fn synced_sample_blends_by_weight() {
+        let mut clips = [clip(1.0, 1.0), clip(2.0, 2.0)];
+        let weights = [
+            ClipWeight {
+                clip: 0,
+                offset: 0.0,
+                weight: 0.25,
+            },
+            ClipWeight {
+                clip: 1,
+                offset: 0.0,
+                weight: 0.75,
+            },
+        ];
+
+        let mut rest = Pose::new();
+        rest.resize(1);
+        let mut out = Pose::new();
+        sample_synced(&weights, &mut clips, &rest, 0.25, &mut out);
+
+        // a quarter through, the first clip is at 0.25 and the second at 0.5
+        let x = out.joints[0].translation.x;
+        assert!(close(x, 0.25 * 0.25 + 0.5 * 0.75), "{x}");
+    }
+}
@@ -3,11 +3,13 @@
 // yet again lots of help from "gabor szauer - hands on c++ game animation programming packt"
 
 use crate::src::animation::pose::Pose;
+use crate::src::animation::track_morph::MorphTrack;
 use crate::src::animation::track_transform::TransformTrack;
 
 #[derive(Clone)]
 pub struct Clip {
     pub tracks: Vec<TransformTrack>,
+    pub morph_tracks: Vec<MorphTrack>,
     pub name: String,
     start_time: f32,
     end_time: f32,
@@ -18,6 +20,7 @@ impl Clip {
     pub fn new() -> Self {
         Self {
             tracks: Vec::new(),
+            morph_tracks: Vec::new(),
             name: String::from("None"),
             start_time: 0.0,
             end_time: 0.0,
@@ -44,6 +47,20 @@ impl Clip {
         time
     }
 
+    /// morph target weights of a node at the given time
+    /// weights without animation keep the values passed in
+    pub fn sample_morph(&self, node: u32, out: &mut [f32], time: f32) {
+        if self.get_duration() == 0.0 {
+            return;
+        }
+
+        let time = self.adjust_time_to_fit_range(time);
+
+        if let Some(track) = self.morph_tracks.iter().find(|track| track.id == node) {
+            track.sample(out, time, self.looping);
+        }
+    }
+
     pub fn adjust_time_to_fit_range(&self, time: f32) -> f32 {
         let mut time = time;
         if self.looping {
@@ -77,27 +94,60 @@ impl Clip {
         let mut start_set = false;
         let mut end_set = false;
 
-        let track_len = self.tracks.len();
-
-        for i in 0..track_len {
-            if self.tracks[i].is_valid() {
-                let start_time = self.tracks[i].get_start_time();
-                let end_time = self.tracks[i].get_end_time();
-
-                if start_time < self.start_time || !start_set {
-                    self.start_time = start_time;
-                    start_set = true;
-                }
+        let transform_ranges = self
+            .tracks
+            .iter()
+            .filter(|track| track.is_valid())
+            .map(|track| (track.get_start_time(), track.get_end_time()));
+        let morph_ranges = self
+            .morph_tracks
+            .iter()
+            .filter(|track| track.is_valid())
+            .map(|track| (track.get_start_time(), track.get_end_time()));
+
+        for (start_time, end_time) in transform_ranges.chain(morph_ranges) {
+            if start_time < self.start_time || !start_set {
+                self.start_time = start_time;
+                start_set = true;
+            }
 
-                if end_time > self.end_time || !end_set {
-                    self.end_time = end_time;
-                    end_set = true;
-                }
+            if end_time > self.end_time || !end_set {
+                self.end_time = end_time;
+                end_set = true;
             }
         }
     }
 
+    pub fn get_start_time(&self) -> f32 {
+        self.start_time
+    }
+
     pub fn get_duration(&self) -> f32 {
         self.end_time - self.start_time
     }
 }
+
+/// clips for the animation tests
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::src::animation::curves::Interpolation;
+    use crate::src::animation::frame::Frame;
+
+    /// clip moving joint 0 along x from 0 to distance over duration seconds
+    pub fn slide(duration: f32, distance: f32) -> Clip {
+        let mut track = TransformTrack::new();
+        track.position.interpolation = Interpolation::Linear;
+        for (time, x) in [(0.0, 0.0), (duration, distance)] {
+            let mut frame = Frame::new();
+            frame.time = time;
+            frame.m_value = [x, 0.0, 0.0];
+            track.position.frames.push(frame);
+        }
+
+        let mut clip = Clip::new();
+        clip.tracks.push(track);
+        clip.re_calculate_duration();
+        clip
+    }
+}
@@ -0,0 +1,65 @@
+// _______________________________________________________________________________________________________
+// _______________________________________________________________________________________________________
+// clips layered over the base animation, optionally limited to part of the body by a mask
+
+use crate::src::animation::clip::Clip;
+use crate::src::animation::mask::JointMask;
+use crate::src::animation::pose::Pose;
+use crate::src::math::transform::Transform;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LayerMode {
+    /// clip made additive(see additive.rs), its offsets get added on top
+    Additive,
+    /// clip replaces the pose below it by the layers weight, e.g. an upper body wave
+    Override,
+}
+
+#[derive(Clone)]
+pub struct Layer {
+    pub clip: Clip,
+    pub mode: LayerMode,
+    /// 0 turns the layer off, 1 applies it fully
+    pub weight: f32,
+    /// joints the layer touches, all of them if None
+    pub mask: Option<JointMask>,
+    time: f32,
+    pose: Pose,
+}
+
+impl Layer {
+    pub fn new(clip: Clip, mode: LayerMode, weight: f32) -> Self {
+        Self {
+            clip,
+            mode,
+            weight,
+            mask: None,
+            time: 0.0,
+            pose: Pose::new(),
+        }
+    }
+
+    /// advance the layer by dt and put it onto pose
+    pub fn apply(&mut self, pose: &mut Pose, rest: &Pose, dt: f32) {
+        match self.mode {
+            LayerMode::Additive => {
+                // joints the clip doesn't touch add nothing
+                self.pose.parents.clone_from(&pose.parents);
+                self.pose.joints.clear();
+                self.pose
+                    .joints
+                    .resize(pose.joints.len(), Transform::DEFAULT);
+            }
+            LayerMode::Override => self.pose.clone_from(rest),
+        }
+
+        self.time = self.clip.sample(&mut self.pose, self.time + dt);
+
+        match (self.mode, &self.mask) {
+            (LayerMode::Additive, Some(mask)) => pose.add_masked(&self.pose, self.weight, mask),
+            (LayerMode::Additive, None) => pose.add(&self.pose, self.weight),
+            (LayerMode::Override, Some(mask)) => pose.blend_masked(&self.pose, self.weight, mask),
+            (LayerMode::Override, None) => pose.blend(&self.pose, self.weight),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// _______________________________________________________________________________________________________
+// _______________________________________________________________________________________________________
+// per joint weights limiting blends and layers to part of the body
+// e.g. a wave on "spine_01" and everything below it, over legs still walking
+
+use crate::src::animation::skeleton::Skeleton;
+use crate::src::scene::file::{self, number};
+
+use json::{object, JsonValue};
+
+use std::fs;
+use std::path::Path;
+
+/// weight per joint, parallel to the skeletons joints
+/// 0 leaves a joint alone and 1 lets a blend or layer take it over fully
+#[derive(Clone, Debug, PartialEq)]
+pub struct JointMask {
+    pub weights: Vec<f32>,
+}
+
+impl JointMask {
+    /// masks out every joint
+    pub fn empty(skeleton: &Skeleton) -> Self {
+        Self {
+            weights: vec![0.0; skeleton.rest_pose.joints.len()],
+        }
+    }
+
+    /// lets every joint through
+    pub fn full(skeleton: &Skeleton) -> Self {
+        Self {
+            weights: vec![1.0; skeleton.rest_pose.joints.len()],
+        }
+    }
+
+    /// joints outside the mask(e.g. from a different skeleton) count as masked out
+    pub fn weight(&self, joint: usize) -> f32 {
+        self.weights.get(joint).copied().unwrap_or(0.0)
+    }
+
+    fn find(skeleton: &Skeleton, name: &str) -> Result<usize, String> {
+        skeleton
+            .joint_names
+            .iter()
+            .position(|n| n == name)
+            .ok_or(format!("skeleton has no joint named \"{name}\""))
+    }
+
+    /// set a single joint
+    pub fn set_joint(
+        &mut self,
+        skeleton: &Skeleton,
+        name: &str,
+        weight: f32,
+    ) -> Result<(), String> {
+        let joint = Self::find(skeleton, name)?;
+        self.weights[joint] = weight;
+        Ok(())
+    }
+
+    /// set a joint and everything below it
+    pub fn set_subtree(
+        &mut self,
+        skeleton: &Skeleton,
+        name: &str,
+        weight: f32,
+    ) -> Result<(), String> {
+        let root = Self::find(skeleton, name)? as i32;
+        let parents = &skeleton.rest_pose.parents;
+
+        for joint in 0..self.weights.len() {
+            // walk up until the root or the top of the hierarchy
+            let mut p = joint as i32;
+            while p >= 0 && p != root {
+                p = parents[p as usize];
+            }
+            if p == root {
+                self.weights[joint] = weight;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the mask with every weight flipped, e.g. lower body from an upper body mask
+    pub fn inverted(&self) -> Self {
+        Self {
+            weights: self.weights.iter().map(|w| 1.0 - w).collect(),
+        }
+    }
+
+    //_______________________________________________________________________________________________
+    // masks get saved by joint name so they survive skeletons being re-exported
+
+    /// { "subtrees": { "spine_01": 1.0 }, "joints": { "neck_01": 0.5 } }
+    /// both are optional and start from an empty mask, subtrees get set before single joints
+    pub fn from_json(value: &JsonValue, ctx: &str, skeleton: &Skeleton) -> Result<Self, String> {
+        let mut mask = Self::empty(skeleton);
+
+        for (key, subtree) in [("subtrees", true), ("joints", false)] {
+            if !value.has_key(key) {
+                continue;
+            }
+            let entries = file::field(value, ctx, key)?;
+            if !entries.is_object() {
+                return Err(format!("`{ctx}.{key}` should be an object"));
+            }
+
+            let ctx = format!("{ctx}.{key}");
+            for (name, _) in entries.entries() {
+                let weight = file::read_f32(entries, &ctx, name)?;
+                let set = if subtree {
+                    mask.set_subtree(skeleton, name, weight)
+                } else {
+                    mask.set_joint(skeleton, name, weight)
+                };
+                set.map_err(|e| format!("`{ctx}.{name}`: {e}"))?;
+            }
+        }
+
+        Ok(mask)
+    }
+
+    /// every joint with a weight, by name
+    pub fn to_json(&self, skeleton: &Skeleton) -> JsonValue {
+        let mut joints = object! {};
+        for (weight, name) in self.weights.iter().zip(skeleton.joint_names.iter()) {
+            if *weight != 0.0 {
+                joints[name.as_str()] = number(*weight);
+            }
+        }
+
+        object! { "joints": joints }
+    }
+
+    pub fn load(path: &Path, skeleton: &Skeleton) -> Result<Self, String> {
+        let src = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let root = json::parse(&src).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        Self::from_json(&root, "mask", skeleton).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path, skeleton: &Skeleton) -> Result<(), String> {
+        fs::write(path, json::stringify_pretty(self.to_json(skeleton), 4))
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// root with a spine(arm, hand below it) and a leg
+    fn skeleton() -> Skeleton {
+        let mut skeleton = Skeleton::new();
+        skeleton.rest_pose.resize(5);
+        skeleton.rest_pose.parents = vec![-1, 0, 1, 2, 0];
+        skeleton.joint_names = ["root", "spine", "arm", "hand", "leg"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        skeleton
+    }
+
+    #[test]
+    fn subtree_covers_exactly_the_descendants() {
+        let skeleton = skeleton();
+        let mut mask = JointMask::empty(&skeleton);
+        mask.set_subtree(&skeleton, "spine", 1.0).unwrap();
+        assert_eq!(mask.weights, [0.0, 1.0, 1.0, 1.0, 0.0]);
+
+        mask.set_subtree(&skeleton, "hand", 0.5).unwrap();
+        assert_eq!(mask.weights, [0.0, 1.0, 1.0, 0.5, 0.0]);
+
+        assert_eq!(mask.inverted().weights, [1.0, 0.0, 0.0, 0.5, 1.0]);
+        assert_eq!(mask.weight(5), 0.0);
+    }
+
+    #[test]
+    fn unknown_joints_get_rejected() {
+        let skeleton = skeleton();
+        let mut mask = JointMask::full(&skeleton);
+        assert_eq!(
+            mask.set_subtree(&skeleton, "tail", 1.0),
+            Err(String::from("skeleton has no joint named \"tail\""))
+        );
+
+        let value = json::object! { "joints": { "tail": 1.0 } };
+        assert_eq!(
+            JointMask::from_json(&value, "mask", &skeleton),
+            Err(String::from(
+                "`mask.joints.tail`: skeleton has no joint named \"tail\""
+            ))
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let skeleton = skeleton();
+        let value = json::object! {
+            "subtrees": { "spine": 1.0 },
+            "joints": { "hand": 0.5, "leg": 0.25 },
+        };
+        let mask = JointMask::from_json(&value, "mask", &skeleton).unwrap();
+        assert_eq!(mask.weights, [0.0, 1.0, 1.0, 0.5, 0.25]);
+
+        let saved = json::parse(&json::stringify(mask.to_json(&skeleton))).unwrap();
+        assert_eq!(JointMask::from_json(&saved, "mask", &skeleton), Ok(mask));
+    }
+}
@@ -1,8 +1,14 @@
+pub mod additive;
 pub mod basic;
+pub mod blend_space;
 pub mod clip;
 pub mod curves;
 pub mod frame;
+pub mod layer;
+pub mod mask;
 pub mod pose;
 pub mod skeleton;
+pub mod state_machine;
 pub mod track;
+pub mod track_morph;
 pub mod track_transform;
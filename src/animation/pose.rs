@@ -1,4 +1,8 @@
-use crate::src::{math::mat4::Mat4, math::transform::Transform};
+use crate::src::animation::mask::JointMask;
+use crate::src::animation::track::Neighborhood;
+use crate::src::{
+    math::mat4::Mat4, math::quaternion::Quat, math::transform::Transform, math::vec3::Vec3,
+};
 
 #[derive(Clone)]
 pub struct Pose {
@@ -13,7 +17,7 @@ impl Pose {
             parents: Vec::new(),
         }
     }
-    pub fn get_global_tranform(&mut self, i: usize) -> Transform {
+    pub fn get_global_tranform(&self, i: usize) -> Transform {
         let mut result = self.joints[i];
         let mut p = self.parents[i];
 
@@ -31,6 +35,51 @@ impl Pose {
         self.joints.resize(new_len, Transform::DEFAULT);
     }
 
+    /// move every joint towards the same joint in other, t = 0 keeps this pose and 1 gives other  
+    /// rotations get flipped into the same hemisphere first so they blend the short way around
+    pub fn blend(&mut self, other: &Pose, t: f32) {
+        self.blend_by(other, |_| t);
+    }
+
+    /// blend that only moves joints the mask lets through, scaled by their mask weight
+    pub fn blend_masked(&mut self, other: &Pose, t: f32, mask: &JointMask) {
+        self.blend_by(other, |joint| t * mask.weight(joint));
+    }
+
+    fn blend_by(&mut self, other: &Pose, t: impl Fn(usize) -> f32) {
+        let joints = self.joints.iter_mut().zip(other.joints.iter());
+        for (i, (joint, target)) in joints.enumerate() {
+            let mut target = *target;
+            Quat::neighborhood(&joint.orientation, &mut target.orientation);
+            *joint = joint.lerp(&target, t(i));
+        }
+    }
+
+    /// layer an additive pose(offsets from a reference, see additive.rs) on top of this one  
+    /// translations add up, rotations and scales multiply in, all scaled down by weight
+    pub fn add(&mut self, additive: &Pose, weight: f32) {
+        self.add_by(additive, |_| weight);
+    }
+
+    /// additive layering limited to the joints the mask lets through
+    pub fn add_masked(&mut self, additive: &Pose, weight: f32, mask: &JointMask) {
+        self.add_by(additive, |joint| weight * mask.weight(joint));
+    }
+
+    fn add_by(&mut self, additive: &Pose, weight: impl Fn(usize) -> f32) {
+        let joints = self.joints.iter_mut().zip(additive.joints.iter());
+        for (i, (joint, offset)) in joints.enumerate() {
+            let weight = weight(i);
+            joint.translation = joint.translation + offset.translation * weight;
+
+            let mut rotation = offset.orientation;
+            Quat::neighborhood(&Quat::ZERO, &mut rotation);
+            joint.orientation = (joint.orientation * Quat::ZERO.nlerp(rotation, weight)).unit();
+
+            joint.scaling = joint.scaling * Vec3::ONE.mix(offset.scaling, weight);
+        }
+    }
+
     pub fn get_matrix_palette(&mut self, out: &mut Vec<Mat4>) {
         let len = self.joints.len();
         if out.len() != len {
@@ -82,3 +131,89 @@ impl PartialEq for Pose {
         return !self.eq(other);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(angle: f32) -> Quat {
+        Quat::create(angle, Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    /// same rotation, q and -q included
+    fn same_rotation(a: Quat, b: Quat) -> bool {
+        (a.dot(&b).abs() - 1.0).abs() < 1e-4
+    }
+
+    fn pose(len: usize) -> Pose {
+        let mut pose = Pose::new();
+        pose.resize(len);
+        pose
+    }
+
+    fn mask(weights: &[f32]) -> JointMask {
+        JointMask {
+            weights: weights.to_vec(),
+        }
+    }
+
+    #[test]
+    fn add_masked_scales_by_the_mask() {
+        let mut offset = pose(3);
+        for joint in offset.joints.iter_mut() {
+            joint.translation = Vec3::new(2.0, 0.0, 0.0);
+            joint.orientation = turn(90.0);
+            joint.scaling = Vec3::new(3.0, 3.0, 3.0);
+        }
+
+        let mut out = pose(3);
+        out.add_masked(&offset, 1.0, &mask(&[1.0, 0.5, 0.0]));
+
+        let x: Vec<f32> = out.joints.iter().map(|j| j.translation.x).collect();
+        assert_eq!(x, [2.0, 1.0, 0.0]);
+        let scale: Vec<f32> = out.joints.iter().map(|j| j.scaling.x).collect();
+        assert_eq!(scale, [3.0, 2.0, 1.0]);
+
+        assert!(same_rotation(out.joints[0].orientation, turn(90.0)));
+        assert!(same_rotation(out.joints[1].orientation, turn(45.0)));
+        assert!(same_rotation(out.joints[2].orientation, Quat::ZERO));
+    }
+
+    #[test]
+    fn add_takes_the_short_way_around() {
+        // -q is the same rotation as q, half of it should still be 45 degrees and not 135
+        let mut offset = pose(1);
+        offset.joints[0].orientation = -turn(90.0);
+
+        let mut out = pose(1);
+        out.add(&offset, 0.5);
+        assert!(same_rotation(out.joints[0].orientation, turn(45.0)));
+    }
+
+    #[test]
+    fn blend_masked_leaves_masked_out_joints_alone() {
+        let mut target = pose(2);
+        for joint in target.joints.iter_mut() {
+            joint.translation = Vec3::new(4.0, 0.0, 0.0);
+            joint.orientation = turn(90.0);
+        }
+
+        let mut out = pose(2);
+        out.blend_masked(&target, 0.5, &mask(&[1.0, 0.0]));
+
+        assert_eq!(out.joints[0].translation.x, 2.0);
+        assert!(same_rotation(out.joints[0].orientation, turn(45.0)));
+        assert_eq!(out.joints[1].translation.x, 0.0);
+        assert!(same_rotation(out.joints[1].orientation, Quat::ZERO));
+    }
+
+    #[test]
+    fn blend_takes_the_short_way_around() {
+        let mut target = pose(1);
+        target.joints[0].orientation = -turn(90.0);
+
+        let mut out = pose(1);
+        out.blend(&target, 0.5);
+        assert!(same_rotation(out.joints[0].orientation, turn(45.0)));
+    }
+}
@@ -2,11 +2,22 @@ use crate::src::animation::pose::Pose;
 
 use crate::src::math::mat4::Mat4;
 
-/// rest pose joints are parallel to inverse bind pose which are also parallel to joint names
+/// set of joints a mesh is bound to  
+/// vertex bone ids index into `joints`, which in turn index into the rest pose
+/// a skeleton can carry several of these(e.g. a body plus separately skinned hair)
+#[derive(Clone)]
+pub struct Skin {
+    pub name: String,
+    pub joints: Vec<usize>,
+    /// parallel to joints
+    pub inverse_bind_mats: Vec<Mat4>,
+}
+
+/// rest pose joints are parallel to joint names
 #[derive(Clone)]
 pub struct Skeleton {
     pub rest_pose: Pose,
-    pub inverse_bind_pose: Vec<Option<Mat4>>,
+    pub skins: Vec<Skin>,
     pub joint_names: Vec<String>,
 }
 
@@ -14,7 +25,7 @@ impl Skeleton {
     pub fn new() -> Self {
         Self {
             rest_pose: Pose::new(),
-            inverse_bind_pose: Vec::new(),
+            skins: Vec::new(),
             joint_names: Vec::new(),
         }
     }
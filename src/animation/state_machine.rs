@@ -0,0 +1,945 @@
+// _______________________________________________________________________________________________________
+// _______________________________________________________________________________________________________
+// animation state machine, gameplay code only sets parameters(speed, grounded, jump...)
+// and the machine works out which states play and fades between them
+
+use crate::src::animation::blend_space::{
+    clip_time, sample_synced, synced_duration, BlendSpace1D, BlendSpace2D, ClipWeight,
+};
+use crate::src::animation::clip::Clip;
+use crate::src::animation::pose::Pose;
+use crate::src::scene::file;
+
+use json::JsonValue;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Parameter {
+    Float(f32),
+    Bool(bool),
+    /// stays set until a transition using it goes through
+    Trigger(bool),
+}
+
+/// named values the transitions look at
+#[derive(Clone, Debug, Default)]
+pub struct Parameters {
+    values: HashMap<String, Parameter>,
+}
+
+impl Parameters {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Parameter> {
+        self.values.get(name).copied()
+    }
+
+    /// 0 for missing parameters and ones that aren't floats
+    pub fn float(&self, name: &str) -> f32 {
+        match self.get(name) {
+            Some(Parameter::Float(value)) => value,
+            _ => 0.0,
+        }
+    }
+
+    /// setting a parameter that doesn't exist yet adds it
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.values
+            .insert(name.to_string(), Parameter::Float(value));
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.values.insert(name.to_string(), Parameter::Bool(value));
+    }
+
+    pub fn set_trigger(&mut self, name: &str) {
+        self.values
+            .insert(name.to_string(), Parameter::Trigger(true));
+    }
+
+    pub fn reset_trigger(&mut self, name: &str) {
+        self.values
+            .insert(name.to_string(), Parameter::Trigger(false));
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// float parameter above the value
+    Greater(String, f32),
+    /// float parameter below the value
+    Less(String, f32),
+    /// bool parameter equal to the value
+    Is(String, bool),
+    /// trigger parameter that's been set
+    Trigger(String),
+}
+
+impl Condition {
+    /// parameters of the wrong type never pass
+    fn passes(&self, parameters: &Parameters) -> bool {
+        match self {
+            Self::Greater(name, value) => {
+                matches!(parameters.get(name), Some(Parameter::Float(v)) if v > *value)
+            }
+            Self::Less(name, value) => {
+                matches!(parameters.get(name), Some(Parameter::Float(v)) if v < *value)
+            }
+            Self::Is(name, value) => {
+                matches!(parameters.get(name), Some(Parameter::Bool(v)) if v == *value)
+            }
+            Self::Trigger(name) => parameters.get(name) == Some(Parameter::Trigger(true)),
+        }
+    }
+}
+
+/// what a state plays, clips are indices into the models animations
+#[derive(Clone, Debug, PartialEq)]
+pub enum Motion {
+    Clip(usize),
+    /// two clips played together and mixed by a float parameter, 0 gives a and 1 gives b
+    Blend {
+        a: usize,
+        b: usize,
+        parameter: String,
+    },
+    /// phase synced, the state time counts cycles instead of seconds
+    BlendSpace1D(BlendSpace1D),
+    /// phase synced like BlendSpace1D
+    BlendSpace2D(BlendSpace2D),
+}
+
+impl Motion {
+    /// clip weights for blend spaces, None for everything else
+    fn weights(&self, parameters: &Parameters) -> Option<Vec<ClipWeight>> {
+        match self {
+            Self::BlendSpace1D(space) => Some(space.weights(parameters.float(&space.parameter))),
+            Self::BlendSpace2D(space) => {
+                Some(space.weights([parameters.float(&space.x), parameters.float(&space.y)]))
+            }
+            _ => None,
+        }
+    }
+
+    /// move time on by dt seconds
+    fn advance(&self, time: f32, dt: f32, clips: &[Clip], parameters: &Parameters) -> f32 {
+        let Some(weights) = self.weights(parameters) else {
+            return time + dt;
+        };
+
+        // the whole blend speeds up or slows down together so the clips stay in phase
+        let duration = synced_duration(&weights, clips);
+        if duration > 0.0 {
+            time + dt / duration
+        } else {
+            time
+        }
+    }
+
+    /// how many passes through the motion time adds up to
+    fn progress(&self, time: f32, clips: &[Clip], parameters: &Parameters) -> f32 {
+        let duration = |clip: &usize| clips.get(*clip).map(|c| c.get_duration()).unwrap_or(0.0);
+        let duration = match self {
+            Self::Clip(clip) => duration(clip),
+            Self::Blend { a, b, parameter } => {
+                let t = parameters.float(parameter).clamp(0.0, 1.0);
+                duration(a) * (1.0 - t) + duration(b) * t
+            }
+            Self::BlendSpace1D(_) | Self::BlendSpace2D(_) => return time,
+        };
+
+        // motions without any length are always done
+        if duration > 0.0 {
+            time / duration
+        } else {
+            f32::INFINITY
+        }
+    }
+
+    /// sample the motion on top of the rest pose
+    fn sample(
+        &self,
+        clips: &mut [Clip],
+        parameters: &Parameters,
+        rest: &Pose,
+        time: f32,
+        out: &mut Pose,
+    ) {
+        out.clone_from(rest);
+        match self {
+            Self::Clip(clip) => {
+                if let Some(clip) = clips.get_mut(*clip) {
+                    clip.sample(out, time);
+                }
+            }
+            Self::Blend { a, b, parameter } => {
+                if let Some(clip) = clips.get_mut(*a) {
+                    clip.sample(out, time);
+                }
+                let mut other = rest.clone();
+                if let Some(clip) = clips.get_mut(*b) {
+                    clip.sample(&mut other, time);
+                }
+                out.blend(&other, parameters.float(parameter).clamp(0.0, 1.0));
+            }
+            Self::BlendSpace1D(_) | Self::BlendSpace2D(_) => {
+                let weights = self.weights(parameters).unwrap_or_default();
+                sample_synced(&weights, clips, rest, time, out);
+            }
+        }
+    }
+
+    /// clip with the most say in the pose and where it's at
+    fn main_clip(
+        &self,
+        time: f32,
+        clips: &[Clip],
+        parameters: &Parameters,
+    ) -> Option<(usize, f32)> {
+        match self {
+            Self::Clip(clip) => Some((*clip, time)),
+            Self::Blend { a, b, parameter } => {
+                if parameters.float(parameter) < 0.5 {
+                    Some((*a, time))
+                } else {
+                    Some((*b, time))
+                }
+            }
+            Self::BlendSpace1D(_) | Self::BlendSpace2D(_) => {
+                let weights = self.weights(parameters)?;
+                let main = weights
+                    .iter()
+                    .max_by(|a, b| a.weight.total_cmp(&b.weight))?;
+                let clip = clips.get(main.clip)?;
+                Some((main.clip, clip_time(main, clip, time)))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    pub name: String,
+    pub motion: Motion,
+    /// playback speed, 1 plays clips as authored
+    pub speed: f32,
+}
+
+impl State {
+    pub fn new(name: &str, motion: Motion) -> Self {
+        Self {
+            name: name.to_string(),
+            motion,
+            speed: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    /// state the transition leaves from, None goes from any state
+    pub from: Option<usize>,
+    pub to: usize,
+    /// all of them have to pass
+    pub conditions: Vec<Condition>,
+    /// seconds spent fading over to the new state
+    pub duration: f32,
+    /// how far through the current pass of the state(0 to 1) it has to be first,
+    /// values past 1 wait for that many passes and then stay open
+    pub exit_time: Option<f32>,
+}
+
+impl Transition {
+    pub fn new(from: Option<usize>, to: usize) -> Self {
+        Self {
+            from,
+            to,
+            conditions: Vec::new(),
+            duration: 0.0,
+            exit_time: None,
+        }
+    }
+}
+
+/// a state and how long it's been playing for
+#[derive(Clone, Copy)]
+struct Playing {
+    state: usize,
+    time: f32,
+}
+
+/// what gets faded out
+#[derive(Clone)]
+enum FadeSource {
+    State(Playing),
+    /// the blended pose at the moment a fade got interrupted by another transition
+    Frozen(Pose),
+}
+
+#[derive(Clone)]
+struct Fade {
+    from: FadeSource,
+    duration: f32,
+    elapsed: f32,
+}
+
+#[derive(Clone)]
+pub struct StateMachine {
+    /// file the machine was loaded from, empty for machines built in code
+    pub file: String,
+    pub parameters: Parameters,
+    pub states: Vec<State>,
+    /// checked in order, the first one that passes gets taken
+    pub transitions: Vec<Transition>,
+    /// state played when the machine starts
+    pub default_state: usize,
+    current: Option<Playing>,
+    fade: Option<Fade>,
+    pose: Pose,
+    /// pose of the state being faded out
+    from_pose: Pose,
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self {
+            file: String::new(),
+            parameters: Parameters::new(),
+            states: Vec::new(),
+            transitions: Vec::new(),
+            default_state: 0,
+            current: None,
+            fade: None,
+            pose: Pose::new(),
+            from_pose: Pose::new(),
+        }
+    }
+
+    /// machine that only loops a single clip
+    pub fn single(clip: usize, name: &str) -> Self {
+        let mut machine = Self::new();
+        machine.add_state(State::new(name, Motion::Clip(clip)));
+        machine
+    }
+
+    /// gives back the states index
+    pub fn add_state(&mut self, state: State) -> usize {
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    pub fn find_state(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|s| s.name == name)
+    }
+
+    /// state playing at full weight once any fade is done
+    pub fn current_state(&self) -> Option<usize> {
+        self.current.map(|c| c.state)
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// clip of the current state with the most weight and its playback time
+    pub fn current_clip(&self, clips: &[Clip]) -> Option<(usize, f32)> {
+        let current = self.current?;
+        let state = &self.states[current.state];
+        state
+            .motion
+            .main_clip(current.time, clips, &self.parameters)
+    }
+
+    /// blended pose from the last update
+    pub fn pose(&self) -> &Pose {
+        &self.pose
+    }
+
+    /// jump to a state straight away
+    pub fn play_state(&mut self, state: usize) {
+        if state < self.states.len() {
+            self.current = Some(Playing { state, time: 0.0 });
+            self.fade = None;
+        }
+    }
+
+    /// fade over to a state whatever the transitions say, asking for the playing state does nothing
+    pub fn fade_to_state(&mut self, state: usize, duration: f32) {
+        if self.current_state() != Some(state) {
+            self.start_fade(state, duration);
+        }
+    }
+
+    /// fade into a state from the start, the playing one included
+    fn start_fade(&mut self, state: usize, duration: f32) {
+        if state >= self.states.len() {
+            return;
+        }
+        let Some(current) = self.current else {
+            self.play_state(state);
+            return;
+        };
+
+        // fading out a fade that isn't done freezes it where it is
+        let from = if self.fade.is_some() {
+            FadeSource::Frozen(self.pose.clone())
+        } else {
+            FadeSource::State(current)
+        };
+        self.fade = Some(Fade {
+            from,
+            duration,
+            elapsed: 0.0,
+        });
+        self.current = Some(Playing { state, time: 0.0 });
+    }
+
+    /// first transition that can be taken from the playing state, previous is its time last update
+    fn pick_transition(&self, clips: &[Clip], previous: f32) -> Option<usize> {
+        let current = self.current?;
+        let state = &self.states[current.state];
+
+        let progress = state.motion.progress(current.time, clips, &self.parameters);
+        let previous = state.motion.progress(previous, clips, &self.parameters);
+        let exited = |exit: f32| {
+            if exit >= 1.0 || progress.is_infinite() {
+                progress >= exit
+            } else {
+                // clips loop so every pass gets its own chance,
+                // a pass that ended since the last update went past any exit time too
+                progress.fract() >= exit || progress.floor() > previous.floor()
+            }
+        };
+
+        // any state transitions win over the states own
+        let any = self.transitions.iter().enumerate().filter(|(_, t)| {
+            // otherwise they'd keep restarting the state they lead to
+            t.from.is_none() && t.to != current.state
+        });
+        let own = self
+            .transitions
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.from == Some(current.state));
+
+        any.chain(own)
+            .find(|(_, t)| {
+                t.to < self.states.len()
+                    && t.exit_time.map(exited).unwrap_or(true)
+                    && t.conditions.iter().all(|c| c.passes(&self.parameters))
+            })
+            .map(|(i, _)| i)
+    }
+
+    /// advance by dt, take whatever transition passes and blend the result on top of the rest pose
+    pub fn update(&mut self, clips: &mut [Clip], rest: &Pose, dt: f32) {
+        if self.current.is_none() {
+            self.play_state(self.default_state);
+        }
+        let Some(current) = self.current.as_mut() else {
+            self.pose.clone_from(rest);
+            return;
+        };
+        let previous = current.time;
+        let state = &self.states[current.state];
+        current.time =
+            state
+                .motion
+                .advance(current.time, dt * state.speed, clips, &self.parameters);
+
+        if let Some(transition) = self.pick_transition(clips, previous) {
+            let transition = self.transitions[transition].clone();
+
+            // triggers get used up by the transition they set off
+            for condition in transition.conditions.iter() {
+                if let Condition::Trigger(name) = condition {
+                    self.parameters.reset_trigger(name);
+                }
+            }
+            // transitions back into the playing state restart it
+            self.start_fade(transition.to, transition.duration);
+        }
+
+        let Some(current) = self.current else {
+            return;
+        };
+        self.states[current.state].motion.sample(
+            clips,
+            &self.parameters,
+            rest,
+            current.time,
+            &mut self.pose,
+        );
+
+        let Some(fade) = self.fade.as_mut() else {
+            return;
+        };
+        match &mut fade.from {
+            FadeSource::State(playing) => {
+                let state = &self.states[playing.state];
+                playing.time =
+                    state
+                        .motion
+                        .advance(playing.time, dt * state.speed, clips, &self.parameters);
+                self.states[playing.state].motion.sample(
+                    clips,
+                    &self.parameters,
+                    rest,
+                    playing.time,
+                    &mut self.from_pose,
+                );
+            }
+            FadeSource::Frozen(pose) => self.from_pose.clone_from(pose),
+        }
+
+        fade.elapsed += dt;
+        let t = if fade.duration > 0.0 {
+            (fade.elapsed / fade.duration).min(1.0)
+        } else {
+            1.0
+        };
+        if t >= 1.0 {
+            self.fade = None;
+            return;
+        }
+
+        self.from_pose.blend(&self.pose, t);
+        std::mem::swap(&mut self.pose, &mut self.from_pose);
+    }
+
+    //_______________________________________________________________________________________________
+    // loading, clips are referred to by name(or index) and states by name
+
+    /// {
+    ///     "parameters": { "speed": 0.0, "vx": 0.0, "vz": 0.0, "pitch": 0.0, "grounded": true, "jump": "trigger" },
+    ///     "default": "idle",
+    ///     "states": [
+    ///         { "name": "idle", "clip": "Idle" },
+    ///         { "name": "move", "blendSpace1D": { "parameter": "speed", "clips": [
+    ///             { "clip": "Walk", "at": 1.5 }, { "clip": "Run", "at": 4.0, "offset": 0.5 }] } },
+    ///         { "name": "strafe", "blendSpace2D": { "x": "vx", "y": "vz", "clips": [
+    ///             { "clip": "Idle", "at": [0, 0] }, { "clip": "WalkLeft", "at": [-1.5, 0] }, ...] } },
+    ///         { "name": "aim", "blend": { "a": "AimDown", "b": "AimUp", "parameter": "pitch" } },
+    ///         { "name": "jump", "clip": "Jump", "speed": 1.2 }
+    ///     ],
+    ///     "transitions": [
+    ///         { "from": "idle", "to": "move", "duration": 0.2,
+    ///           "conditions": [{ "parameter": "speed", "greater": 0.1 }] },
+    ///         { "to": "jump", "conditions": [{ "parameter": "jump" }] }
+    ///     ]
+    /// }
+    /// transitions without "from" go from any state, "default" falls back to the first state
+    /// conditions take one of "greater", "less" or "is", or nothing for triggers  
+    /// blend space clips take an "offset"(0 to 1) lining their cycle up with the others
+    pub fn from_json(value: &JsonValue, ctx: &str, clips: &[Clip]) -> Result<Self, String> {
+        let mut machine = Self::new();
+
+        if value.has_key("parameters") {
+            let parameters = file::field(value, ctx, "parameters")?;
+            if !parameters.is_object() {
+                return Err(format!("`{ctx}.parameters` should be an object"));
+            }
+            for (name, parameter) in parameters.entries() {
+                if let Some(value) = parameter.as_f32() {
+                    machine.parameters.set_float(name, value);
+                } else if let Some(value) = parameter.as_bool() {
+                    machine.parameters.set_bool(name, value);
+                } else if parameter.as_str() == Some("trigger") {
+                    machine.parameters.reset_trigger(name);
+                } else {
+                    return Err(format!(
+                        "`{ctx}.parameters.{name}` should be a number, true/false or \"trigger\""
+                    ));
+                }
+            }
+        }
+
+        for (ctx, state) in file::read_list(value, ctx, "states")? {
+            let name = file::read_str(state, &ctx, "name")?;
+            if machine.find_state(name).is_some() {
+                return Err(format!(
+                    "`{ctx}.name`: there's already a state called \"{name}\""
+                ));
+            }
+
+            let motion = if state.has_key("clip") {
+                Motion::Clip(Self::read_clip(state, &ctx, "clip", clips)?)
+            } else if state.has_key("blend") {
+                let blend = file::field(state, &ctx, "blend")?;
+                let ctx = format!("{ctx}.blend");
+                Motion::Blend {
+                    a: Self::read_clip(blend, &ctx, "a", clips)?,
+                    b: Self::read_clip(blend, &ctx, "b", clips)?,
+                    parameter: machine.read_parameter(blend, &ctx, "parameter", "float")?,
+                }
+            } else if state.has_key("blendSpace1D") {
+                let space = file::field(state, &ctx, "blendSpace1D")?;
+                let ctx = format!("{ctx}.blendSpace1D");
+                let mut blend = BlendSpace1D::new(&machine.read_parameter(
+                    space,
+                    &ctx,
+                    "parameter",
+                    "float",
+                )?);
+
+                for (ctx, point) in file::read_list(space, &ctx, "clips")? {
+                    let (clip, offset) = Self::read_blend_point(point, &ctx, clips)?;
+                    blend.add(file::read_f32(point, &ctx, "at")?, clip, offset);
+                }
+                Motion::BlendSpace1D(blend)
+            } else if state.has_key("blendSpace2D") {
+                let space = file::field(state, &ctx, "blendSpace2D")?;
+                let ctx = format!("{ctx}.blendSpace2D");
+                let mut blend = BlendSpace2D::new(
+                    &machine.read_parameter(space, &ctx, "x", "float")?,
+                    &machine.read_parameter(space, &ctx, "y", "float")?,
+                );
+
+                for (ctx, point) in file::read_list(space, &ctx, "clips")? {
+                    let (clip, offset) = Self::read_blend_point(point, &ctx, clips)?;
+                    blend.add(file::read_array::<2>(point, &ctx, "at")?, clip, offset);
+                }
+                Motion::BlendSpace2D(blend)
+            } else {
+                return Err(format!(
+                    "`{ctx}` needs a \"clip\", \"blend\", \"blendSpace1D\" or \"blendSpace2D\""
+                ));
+            };
+
+            let mut state_entry = State::new(name, motion);
+            if state.has_key("speed") {
+                state_entry.speed = file::read_f32(state, &ctx, "speed")?;
+            }
+            machine.add_state(state_entry);
+        }
+        if machine.states.is_empty() {
+            return Err(format!("`{ctx}.states` is empty"));
+        }
+
+        if value.has_key("default") {
+            machine.default_state = machine.read_state(value, ctx, "default")?;
+        }
+
+        if value.has_key("transitions") {
+            for (ctx, entry) in file::read_list(value, ctx, "transitions")? {
+                let from = if entry.has_key("from") {
+                    Some(machine.read_state(entry, &ctx, "from")?)
+                } else {
+                    None
+                };
+                let mut transition = Transition::new(from, machine.read_state(entry, &ctx, "to")?);
+
+                if entry.has_key("duration") {
+                    transition.duration = file::read_f32(entry, &ctx, "duration")?;
+                }
+                if entry.has_key("exitTime") {
+                    transition.exit_time = Some(file::read_f32(entry, &ctx, "exitTime")?);
+                }
+                if entry.has_key("conditions") {
+                    for (ctx, condition) in file::read_list(entry, &ctx, "conditions")? {
+                        transition
+                            .conditions
+                            .push(machine.read_condition(condition, &ctx)?);
+                    }
+                }
+
+                machine.transitions.push(transition);
+            }
+        }
+
+        Ok(machine)
+    }
+
+    pub fn load(path: &Path, clips: &[Clip]) -> Result<Self, String> {
+        let src = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let root = json::parse(&src).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        let mut machine = Self::from_json(&root, "stateMachine", clips)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        machine.file = path.to_string_lossy().into_owned();
+        Ok(machine)
+    }
+
+    /// clip by name or index
+    fn read_clip(value: &JsonValue, ctx: &str, key: &str, clips: &[Clip]) -> Result<usize, String> {
+        let clip = if let Some(name) = file::field(value, ctx, key)?.as_str() {
+            clips
+                .iter()
+                .position(|c| c.name == name)
+                .ok_or(format!("`{ctx}.{key}`: there's no clip called \"{name}\""))?
+        } else {
+            file::read_usize(value, ctx, key)?
+        };
+
+        if clip >= clips.len() {
+            return Err(format!(
+                "`{ctx}.{key}`: the model only has {} animations",
+                clips.len()
+            ));
+        }
+        Ok(clip)
+    }
+
+    fn read_state(&self, value: &JsonValue, ctx: &str, key: &str) -> Result<usize, String> {
+        let name = file::read_str(value, ctx, key)?;
+        self.find_state(name)
+            .ok_or(format!("`{ctx}.{key}`: there's no state called \"{name}\""))
+    }
+
+    /// clip and cycle offset of a clip placed in a blend space
+    fn read_blend_point(
+        value: &JsonValue,
+        ctx: &str,
+        clips: &[Clip],
+    ) -> Result<(usize, f32), String> {
+        let clip = Self::read_clip(value, ctx, "clip", clips)?;
+        let offset = if value.has_key("offset") {
+            file::read_f32(value, ctx, "offset")?
+        } else {
+            0.0
+        };
+        Ok((clip, offset))
+    }
+
+    /// name of a declared parameter of the kind asked for
+    fn read_parameter(
+        &self,
+        value: &JsonValue,
+        ctx: &str,
+        key: &str,
+        kind: &str,
+    ) -> Result<String, String> {
+        let name = file::read_str(value, ctx, key)?;
+        let found = match self.parameters.get(name) {
+            Some(Parameter::Float(_)) => "float",
+            Some(Parameter::Bool(_)) => "bool",
+            Some(Parameter::Trigger(_)) => "trigger",
+            None => return Err(format!("`{ctx}.{key}`: \"{name}\" isn't in the parameters")),
+        };
+
+        if found != kind {
+            return Err(format!(
+                "`{ctx}.{key}`: \"{name}\" is a {found}, expected a {kind}"
+            ));
+        }
+        Ok(name.to_string())
+    }
+
+    fn read_condition(&self, value: &JsonValue, ctx: &str) -> Result<Condition, String> {
+        if value.has_key("greater") {
+            let name = self.read_parameter(value, ctx, "parameter", "float")?;
+            Ok(Condition::Greater(
+                name,
+                file::read_f32(value, ctx, "greater")?,
+            ))
+        } else if value.has_key("less") {
+            let name = self.read_parameter(value, ctx, "parameter", "float")?;
+            Ok(Condition::Less(name, file::read_f32(value, ctx, "less")?))
+        } else if value.has_key("is") {
+            let name = self.read_parameter(value, ctx, "parameter", "bool")?;
+            Ok(Condition::Is(name, file::read_bool(value, ctx, "is")?))
+        } else {
+            Ok(Condition::Trigger(self.read_parameter(
+                value,
+                ctx,
+                "parameter",
+                "trigger",
+            )?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::src::animation::clip::tests::slide;
+
+    /// one second clips moving joint 0 along x
+    fn clips(names: &[&str]) -> Vec<Clip> {
+        names
+            .iter()
+            .map(|name| {
+                let mut clip = slide(1.0, 1.0);
+                clip.name = name.to_string();
+                clip
+            })
+            .collect()
+    }
+
+    fn rest() -> Pose {
+        let mut pose = Pose::new();
+        pose.resize(1);
+        pose
+    }
+
+    fn load(src: &str, clips: &[Clip]) -> Result<StateMachine, String> {
+        StateMachine::from_json(&json::parse(src).unwrap(), "machine", clips)
+    }
+
+    fn state(machine: &StateMachine) -> &str {
+        &machine.states[machine.current_state().unwrap()].name
+    }
+
+    #[test]
+    fn triggers_get_used_up_once() {
+        let mut clips = clips(&["Idle", "Jump"]);
+        let mut machine = load(
+            r#"{
+                "parameters": { "jump": "trigger" },
+                "states": [{ "name": "idle", "clip": "Idle" }, { "name": "jump", "clip": "Jump" }],
+                "transitions": [
+                    { "from": "idle", "to": "jump", "conditions": [{ "parameter": "jump" }] },
+                    { "from": "jump", "to": "jump", "conditions": [{ "parameter": "jump" }] }
+                ]
+            }"#,
+            &clips,
+        )
+        .unwrap();
+
+        machine.update(&mut clips, &rest(), 0.1);
+        assert_eq!(state(&machine), "idle");
+
+        machine.parameters.set_trigger("jump");
+        machine.update(&mut clips, &rest(), 0.1);
+        assert_eq!(state(&machine), "jump");
+        assert_eq!(
+            machine.parameters.get("jump"),
+            Some(Parameter::Trigger(false))
+        );
+
+        // nothing left to set the self transition off
+        machine.update(&mut clips, &rest(), 0.3);
+        machine.update(&mut clips, &rest(), 0.3);
+        assert!(machine.current_clip(&clips).unwrap().1 > 0.5);
+
+        // setting it again restarts the jump
+        machine.parameters.set_trigger("jump");
+        machine.update(&mut clips, &rest(), 0.1);
+        assert_eq!(state(&machine), "jump");
+        assert_eq!(machine.current_clip(&clips).unwrap().1, 0.0);
+        assert_eq!(
+            machine.parameters.get("jump"),
+            Some(Parameter::Trigger(false))
+        );
+    }
+
+    #[test]
+    fn any_state_transitions_come_first() {
+        let mut clips = clips(&["Idle", "Run", "Fall"]);
+        let mut machine = load(
+            r#"{
+                "parameters": { "speed": 0.0, "grounded": true },
+                "states": [
+                    { "name": "idle", "clip": "Idle" },
+                    { "name": "run", "clip": "Run" },
+                    { "name": "fall", "clip": "Fall" }
+                ],
+                "transitions": [
+                    { "from": "idle", "to": "run", "conditions": [{ "parameter": "speed", "greater": 0.1 }] },
+                    { "to": "fall", "conditions": [{ "parameter": "grounded", "is": false }] }
+                ]
+            }"#,
+            &clips,
+        )
+        .unwrap();
+
+        machine.parameters.set_float("speed", 2.0);
+        machine.parameters.set_bool("grounded", false);
+        machine.update(&mut clips, &rest(), 0.1);
+        assert_eq!(state(&machine), "fall");
+
+        // any state transitions don't restart the state they lead to
+        machine.update(&mut clips, &rest(), 0.1);
+        assert!(machine.current_clip(&clips).unwrap().1 > 0.0);
+    }
+
+    #[test]
+    fn exit_times_wait_for_their_point_in_the_pass() {
+        let mut clips = clips(&["Idle", "Wave"]);
+        let src = r#"{
+            "parameters": { "wave": false },
+            "states": [{ "name": "idle", "clip": "Idle" }, { "name": "wave", "clip": "Wave" }],
+            "transitions": [
+                { "from": "idle", "to": "wave", "exitTime": 0.9, "conditions": [{ "parameter": "wave", "is": true }] }
+            ]
+        }"#;
+
+        let mut machine = load(src, &clips).unwrap();
+        machine.parameters.set_bool("wave", true);
+        machine.update(&mut clips, &rest(), 0.5);
+        assert_eq!(state(&machine), "idle");
+        machine.update(&mut clips, &rest(), 0.45);
+        assert_eq!(state(&machine), "wave");
+
+        // a later pass has to get to the exit time again
+        let mut machine = load(src, &clips).unwrap();
+        machine.update(&mut clips, &rest(), 1.2);
+        machine.parameters.set_bool("wave", true);
+        machine.update(&mut clips, &rest(), 0.05);
+        assert_eq!(state(&machine), "idle");
+        machine.update(&mut clips, &rest(), 0.7);
+        assert_eq!(state(&machine), "wave");
+
+        // skipping past the end of a pass counts as getting there
+        let mut machine = load(src, &clips).unwrap();
+        machine.update(&mut clips, &rest(), 0.5);
+        machine.parameters.set_bool("wave", true);
+        machine.update(&mut clips, &rest(), 0.6);
+        assert_eq!(state(&machine), "wave");
+    }
+
+    #[test]
+    fn parameters_of_the_wrong_kind_get_rejected() {
+        let clips = clips(&["Idle", "Walk"]);
+
+        let error = load(
+            r#"{
+                "parameters": { "grounded": true, "vz": 0.0 },
+                "states": [{ "name": "move", "blendSpace2D": { "x": "grounded", "y": "vz", "clips": [] } }]
+            }"#,
+            &clips,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error,
+            "`machine.states[0].blendSpace2D.x`: \"grounded\" is a bool, expected a float"
+        );
+
+        let error = load(
+            r#"{
+                "parameters": { "grounded": true },
+                "states": [{ "name": "idle", "clip": "Idle" }],
+                "transitions": [{ "to": "idle", "conditions": [{ "parameter": "grounded", "greater": 1.0 }] }]
+            }"#,
+            &clips,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error,
+            "`machine.transitions[0].conditions[0].parameter`: \"grounded\" is a bool, expected a float"
+        );
+
+        let error = load(
+            r#"{
+                "states": [{ "name": "idle", "clip": "Idle" }],
+                "transitions": [{ "to": "idle", "conditions": [{ "parameter": "jump" }] }]
+            }"#,
+            &clips,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error,
+            "`machine.transitions[0].conditions[0].parameter`: \"jump\" isn't in the parameters"
+        );
+    }
+}
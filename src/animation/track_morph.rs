@@ -0,0 +1,48 @@
+use crate::src::animation::track;
+
+/// morph target weight animation for a single node, one scalar track per target
+#[derive(Clone)]
+pub struct MorphTrack {
+    pub id: u32,
+    pub weights: Vec<track::ScalarTrack>,
+}
+
+impl MorphTrack {
+    pub fn new() -> Self {
+        Self {
+            id: 0,
+            weights: Vec::new(),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.weights.iter().any(|track| track.frames.len() > 1)
+    }
+
+    pub fn get_start_time(&self) -> f32 {
+        self.weights
+            .iter()
+            .filter(|track| track.frames.len() > 1)
+            .map(|track| track.get_start_time())
+            .reduce(f32::min)
+            .unwrap_or(0.0)
+    }
+
+    pub fn get_end_time(&self) -> f32 {
+        self.weights
+            .iter()
+            .filter(|track| track.frames.len() > 1)
+            .map(|track| track.get_end_time())
+            .reduce(f32::max)
+            .unwrap_or(0.0)
+    }
+
+    /// overwrite the weights that are animated, the rest keep whatever value they came in with
+    pub fn sample(&self, out: &mut [f32], time: f32, looping: bool) {
+        for (weight, track) in out.iter_mut().zip(self.weights.iter()) {
+            if track.frames.len() > 1 {
+                *weight = track.sample::<f32>(time, looping);
+            }
+        }
+    }
+}
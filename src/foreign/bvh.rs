@@ -0,0 +1,308 @@
+use crate::src::animation::{
+    clip::Clip, curves::Interpolation, frame::Frame, pose::Pose, skeleton::Skeleton,
+    track_transform::TransformTrack,
+};
+use crate::src::math::{quaternion::Quat, transform::Transform, vec3::*};
+use crate::src::renderer::model::Model;
+
+use super::error::LoadError;
+
+use std::fs;
+use std::path::Path;
+
+// biovision hierarchy(.bvh) motion capture loader
+// the hierarchy becomes a skeleton with the offsets as its rest pose
+// and the motion a single clip with a track for every joint that has channels
+// end sites only mark where the last bone ends so they're left out
+// units are left as they are in the file, usually centimeters
+
+#[derive(Clone, Copy, PartialEq)]
+enum Channel {
+    Position(usize),
+    Rotation(usize),
+}
+
+struct BvhJoint {
+    name: String,
+    parent: i32,
+    offset: [f32; 3],
+    channels: Vec<Channel>,
+}
+
+pub struct Bvh {
+    name: String,
+    joints: Vec<BvhJoint>,
+    frame_time: f32,
+    /// one row of channel values per frame, in the order the joints declared them
+    frames: Vec<Vec<f32>>,
+}
+
+/// whitespace separated words with the line they're on for error messages
+struct Tokens<'a> {
+    words: Vec<(usize, &'a str)>,
+    at: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(src: &'a str) -> Self {
+        let words = src
+            .lines()
+            .enumerate()
+            .flat_map(|(number, line)| line.split_whitespace().map(move |w| (number + 1, w)))
+            .collect();
+        Self { words, at: 0 }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.words.get(self.at).map(|(_, w)| *w)
+    }
+
+    fn error(&self, message: &str) -> LoadError {
+        match self
+            .words
+            .get(self.at.min(self.words.len().saturating_sub(1)))
+        {
+            Some((line, _)) => LoadError::Invalid(format!("line {line}: {message}")),
+            None => LoadError::Invalid(message.to_string()),
+        }
+    }
+
+    fn next(&mut self) -> Result<&'a str, LoadError> {
+        let word = self
+            .peek()
+            .ok_or_else(|| self.error("unexpected end of file"))?;
+        self.at += 1;
+        Ok(word)
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), LoadError> {
+        if self.peek() != Some(word) {
+            return Err(self.error(&format!("expected \"{word}\"")));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T, LoadError> {
+        let word = self
+            .peek()
+            .ok_or_else(|| self.error("unexpected end of file"))?;
+        let value = word
+            .parse()
+            .map_err(|_| self.error(&format!("\"{word}\" is not a number")))?;
+        self.at += 1;
+        Ok(value)
+    }
+}
+
+impl Bvh {
+    /// the clip gets named after the file
+    pub fn new(path: &Path) -> Result<Bvh, LoadError> {
+        if !path.is_file() {
+            return Err(LoadError::NotAFile(path.to_path_buf()));
+        }
+
+        let src = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Self::from_str(&src, &name)
+    }
+
+    pub fn from_str(src: &str, name: &str) -> Result<Bvh, LoadError> {
+        let mut bvh = Bvh {
+            name: name.to_string(),
+            joints: Vec::new(),
+            frame_time: 0.0,
+            frames: Vec::new(),
+        };
+        let mut tokens = Tokens::new(src);
+
+        tokens.expect("HIERARCHY")?;
+        tokens.expect("ROOT")?;
+        bvh.read_joint(&mut tokens, -1)?;
+
+        tokens.expect("MOTION")?;
+        tokens.expect("Frames:")?;
+        let frame_count: usize = tokens.number()?;
+        tokens.expect("Frame")?;
+        tokens.expect("Time:")?;
+        bvh.frame_time = tokens.number()?;
+
+        let channel_count = bvh.joints.iter().map(|j| j.channels.len()).sum();
+        for _ in 0..frame_count {
+            let mut values = Vec::with_capacity(channel_count);
+            for _ in 0..channel_count {
+                values.push(tokens.number()?);
+            }
+            bvh.frames.push(values);
+        }
+
+        Ok(bvh)
+    }
+
+    /// reads a joint after its ROOT or JOINT keyword, along with its children
+    fn read_joint(&mut self, tokens: &mut Tokens, parent: i32) -> Result<(), LoadError> {
+        let name = tokens.next()?.to_string();
+        tokens.expect("{")?;
+
+        let index = self.joints.len() as i32;
+        self.joints.push(BvhJoint {
+            name,
+            parent,
+            offset: [0.0; 3],
+            channels: Vec::new(),
+        });
+
+        loop {
+            match tokens.next()? {
+                "OFFSET" => {
+                    let offset = [tokens.number()?, tokens.number()?, tokens.number()?];
+                    self.joints[index as usize].offset = offset;
+                }
+                "CHANNELS" => {
+                    let count: usize = tokens.number()?;
+                    for _ in 0..count {
+                        let channel = match tokens.next()? {
+                            "Xposition" => Channel::Position(0),
+                            "Yposition" => Channel::Position(1),
+                            "Zposition" => Channel::Position(2),
+                            "Xrotation" => Channel::Rotation(0),
+                            "Yrotation" => Channel::Rotation(1),
+                            "Zrotation" => Channel::Rotation(2),
+                            other => {
+                                return Err(tokens.error(&format!("unknown channel \"{other}\"")))
+                            }
+                        };
+                        self.joints[index as usize].channels.push(channel);
+                    }
+                }
+                "JOINT" => self.read_joint(tokens, index)?,
+                "End" => {
+                    tokens.expect("Site")?;
+                    tokens.expect("{")?;
+                    while tokens.next()? != "}" {}
+                }
+                "}" => return Ok(()),
+                other => return Err(tokens.error(&format!("unexpected \"{other}\""))),
+            }
+        }
+    }
+
+    //_______________________________________________________________________________________________
+    pub fn populate_model(&self, model: &mut Model) -> Result<(), LoadError> {
+        self.extract_skeleton(&mut model.skeleton);
+        model.animations.push(self.extract_clip());
+        Ok(())
+    }
+
+    /// skeleton without skins, there are no meshes to bind
+    pub fn extract_skeleton(&self, skeleton: &mut Skeleton) {
+        let mut pose = Pose::new();
+
+        for joint in self.joints.iter() {
+            let mut transform = Transform::DEFAULT;
+            transform.translation = Vec3::from(&joint.offset);
+
+            pose.joints.push(transform);
+            pose.parents.push(joint.parent);
+            skeleton.joint_names.push(joint.name.clone());
+        }
+
+        skeleton.rest_pose = pose;
+    }
+
+    /// one track per joint with channels, keyed every frame
+    pub fn extract_clip(&self) -> Clip {
+        let mut clip = Clip::new();
+        clip.name = self.name.clone();
+
+        let mut first_channel = 0;
+        for (id, joint) in self.joints.iter().enumerate() {
+            let channels = first_channel..first_channel + joint.channels.len();
+            first_channel = channels.end;
+            if joint.channels.is_empty() {
+                continue;
+            }
+
+            let positioned = joint
+                .channels
+                .iter()
+                .any(|c| matches!(c, Channel::Position(_)));
+
+            let mut track = TransformTrack::new();
+            track.id = id as u32;
+            track.position.interpolation = Interpolation::Linear;
+            track.rotation.interpolation = Interpolation::Linear;
+
+            let mut previous = Quat::ZERO;
+            for (f, values) in self.frames.iter().enumerate() {
+                let time = f as f32 * self.frame_time;
+                let (position, rotation) = Self::joint_pose(joint, &values[channels.clone()]);
+
+                // keep neighbouring keys in the same hemisphere so they interpolate the short way
+                let rotation = if previous.dot(&rotation) < 0.0 {
+                    -rotation
+                } else {
+                    rotation
+                };
+                previous = rotation;
+
+                let mut frame = Frame::new();
+                frame.time = time;
+                frame.m_value = rotation.to_array();
+                track.rotation.frames.push(frame);
+
+                if positioned {
+                    let mut frame = Frame::new();
+                    frame.time = time;
+                    frame.m_value = position;
+                    track.position.frames.push(frame);
+                }
+            }
+
+            clip.tracks.push(track);
+        }
+
+        clip.re_calculate_duration();
+        clip
+    }
+
+    /// local position and orientation of a joint from its channel values
+    /// position channels replace the offset on their axis
+    /// rotations are in degrees and apply in the order listed, so "Zrotation Xrotation Yrotation"
+    /// means Z * X * Y
+    fn joint_pose(joint: &BvhJoint, values: &[f32]) -> ([f32; 3], Quat) {
+        const AXES: [Vec3; 3] = [
+            Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            Vec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+        ];
+
+        let mut position = joint.offset;
+        let mut rotation = Quat::ZERO;
+
+        for (channel, value) in joint.channels.iter().zip(values) {
+            match *channel {
+                Channel::Position(axis) => position[axis] = *value,
+                Channel::Rotation(axis) => rotation = rotation * Quat::create(*value, AXES[axis]),
+            }
+        }
+
+        (position, rotation.unit())
+    }
+}
@@ -0,0 +1,773 @@
+use crate::src::animation::{
+    clip::Clip,
+    curves::Interpolation,
+    frame::Frame,
+    pose::Pose,
+    skeleton::{Skeleton, Skin},
+    track::Track,
+    track_morph::MorphTrack,
+    track_transform::TransformTrack,
+};
+use crate::src::math::{mat4::Mat4, quaternion::Quat, transform::Transform, vec3::Vec3};
+use crate::src::renderer::{
+    buffer::*,
+    material::*,
+    mesh::Mesh,
+    model::Model,
+    morph::{Morph, MorphTarget},
+    texture::{Sampling, Texture},
+    vertex::Vertex,
+};
+
+use super::error::LoadError;
+
+use std::collections::{hash_map::Entry, HashMap};
+use std::fs;
+use std::mem::size_of;
+use std::path::Path;
+
+// engine native model cache(.dhm)
+// holds models the way they sit in memory after loading so startup skips parsing,
+// decoding images and pushing vertices one by one
+//
+// layout, little endian:
+//   magic "DHMC", format version u32, payload length u64, fnv-1a checksum of the payload u64
+//   payload: textures, meshes, skeleton, clips
+// vertex and index blocks are copied as they are in memory,
+// which like the rest of the engine assumes a little endian machine
+// textures are read back from the gpu when converting so that needs a gl context
+
+const MAGIC: &[u8; 4] = b"DHMC";
+/// bump whenever the layout changes, older caches then have to be converted again
+pub const VERSION: u32 = 2;
+const HEADER_LEN: usize = 4 + 4 + 8 + 8;
+const NONE: u32 = u32::MAX;
+
+/// types that get copied as raw blocks
+/// # Safety
+/// only for types without padding that any bit pattern is a valid value of,
+/// so reading them straight out of a file can't make something broken
+unsafe trait Plain: Copy {}
+unsafe impl Plain for u8 {}
+unsafe impl Plain for u32 {}
+unsafe impl Plain for f32 {}
+unsafe impl Plain for [f32; 3] {}
+// repr(C) and nothing but f32s and i32s
+unsafe impl Plain for Vertex {}
+
+/// load any model format the engine reads and write it out as a cache
+pub fn convert(source: &Path, out: &Path) -> Result<(), LoadError> {
+    let mut model = Model::default();
+    super::load_model(source, &mut model)?;
+    write(&model, out)
+}
+
+pub fn write(model: &Model, path: &Path) -> Result<(), LoadError> {
+    let mut payload = Writer::new();
+    payload.model(model);
+    let payload = payload.bytes;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    out.extend_from_slice(&checksum(&payload).to_le_bytes());
+    out.extend_from_slice(&payload);
+
+    fs::write(path, out).map_err(|e| LoadError::Io(path.to_path_buf(), e))
+}
+
+/// 64 bit fnv-1a, catches truncated and corrupted files
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub struct Cache {
+    /// checked payload
+    payload: Vec<u8>,
+}
+
+impl Cache {
+    /// reads the whole file in one go and checks its header and checksum
+    pub fn new(path: &Path) -> Result<Cache, LoadError> {
+        if !path.is_file() {
+            return Err(LoadError::NotAFile(path.to_path_buf()));
+        }
+
+        let bytes = fs::read(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+        Self::from_bytes(bytes)
+    }
+
+    pub fn from_bytes(mut bytes: Vec<u8>) -> Result<Cache, LoadError> {
+        if bytes.len() < HEADER_LEN || &bytes[0..4] != MAGIC {
+            return Err(LoadError::Invalid(String::from("not a model cache")));
+        }
+
+        let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        if version != VERSION {
+            return Err(LoadError::Outdated(version));
+        }
+
+        let len = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) as usize;
+        let sum = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
+        let payload = bytes.split_off(HEADER_LEN);
+        if payload.len() != len || checksum(&payload) != sum {
+            return Err(LoadError::Invalid(String::from(
+                "model cache is corrupt, convert it again",
+            )));
+        }
+
+        Ok(Cache { payload })
+    }
+
+    //_______________________________________________________________________________________________
+    pub fn populate_model(&self, model: &mut Model) -> Result<(), LoadError> {
+        let mut reader = Reader {
+            bytes: &self.payload,
+            at: 0,
+        };
+        reader.model(model)
+    }
+}
+
+//___________________________________________________________________________________________________
+// writing
+
+struct Writer {
+    bytes: Vec<u8>,
+    /// gl texture id to index in the texture table
+    textures: HashMap<u32, u32>,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            textures: HashMap::new(),
+        }
+    }
+
+    fn u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+    fn u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    fn i32(&mut self, v: i32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    fn f32(&mut self, v: f32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    fn floats(&mut self, values: &[f32]) {
+        for v in values {
+            self.f32(*v);
+        }
+    }
+    fn len(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+    /// plain old data straight from memory
+    fn block<T: Plain>(&mut self, values: &[T]) {
+        self.len(values.len());
+        let bytes = unsafe {
+            std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+        };
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn transform(&mut self, t: &Transform) {
+        self.floats(&t.translation.to_array());
+        self.floats(&t.scaling.to_array());
+        self.floats(&t.orientation.to_array());
+    }
+
+    fn model(&mut self, model: &Model) {
+        self.texture_table(model);
+        self.len(model.meshes.len());
+        for mesh in model.meshes.iter() {
+            self.mesh(mesh);
+        }
+        self.skeleton(&model.skeleton);
+        self.len(model.animations.len());
+        for clip in model.animations.iter() {
+            self.clip(clip);
+        }
+    }
+
+    /// every texture the model or its meshes hold, once each
+    fn texture_table(&mut self, model: &Model) {
+        let mut held: Vec<&Option<Texture>> = Vec::new();
+        for mesh in model.meshes.iter() {
+            held.push(&mesh.texture);
+            match &mesh.material {
+                Materail::Phong(phong) => {
+                    held.extend([&phong.diffuse_texture, &phong.specular_texture]);
+                }
+                Materail::Pbr(pbr) => held.extend([
+                    &pbr.base_texture,
+                    &pbr.metallic_texture,
+                    &pbr.normal_texture,
+                    &pbr.occlusion_texture,
+                    &pbr.emissive_texture,
+                ]),
+            }
+        }
+
+        let mut table: Vec<&Texture> = Vec::new();
+        let mut lookup = HashMap::new();
+        let textures = model.textures.iter().chain(held.into_iter().flatten());
+        for texture in textures {
+            if let Entry::Vacant(entry) = lookup.entry(texture.id) {
+                entry.insert(table.len() as u32);
+                table.push(texture);
+            }
+        }
+
+        self.len(table.len());
+        for texture in table {
+            let (width, height, pixels) = texture.read_pixels();
+            let sampling = texture.sampling();
+            self.u32(width);
+            self.u32(height);
+            self.block(&pixels);
+            self.u32(sampling.wrap_s);
+            self.u32(sampling.wrap_t);
+            self.u32(sampling.min_filter);
+            self.u32(sampling.mag_filter);
+        }
+        self.textures = lookup;
+    }
+
+    fn texture(&mut self, texture: &Option<Texture>) {
+        let index = texture
+            .as_ref()
+            .and_then(|t| self.textures.get(&t.id).copied())
+            .unwrap_or(NONE);
+        self.u32(index);
+    }
+
+    fn mesh(&mut self, mesh: &Mesh) {
+        // morphed meshes get stored unmorphed, the default weights go back on when loading
+        match &mesh.morph {
+            Some(morph) => self.block(morph.base()),
+            None => self.block(&mesh.vbo.data),
+        }
+        match &mesh.ebo {
+            Some(ebo) => {
+                self.u8(1);
+                self.block(&ebo.data);
+            }
+            None => self.u8(0),
+        }
+
+        self.texture(&mesh.texture);
+        self.material(&mesh.material);
+        self.u32(mesh.skin.map(|s| s as u32).unwrap_or(NONE));
+
+        self.len(mesh.instances.len());
+        for instance in mesh.instances.iter() {
+            self.transform(instance);
+        }
+        self.len(mesh.nodes.len());
+        for node in mesh.nodes.iter() {
+            self.len(*node);
+        }
+
+        match &mesh.morph {
+            Some(morph) => {
+                self.u8(1);
+                self.len(morph.targets.len());
+                for target in morph.targets.iter() {
+                    self.block(&target.positions);
+                    self.block(&target.normals);
+                }
+                self.block(&morph.default_weights);
+                self.len(morph.node);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        self.u8(match pattern.kind {
+            PatternKind::None => 0,
+            PatternKind::Checkered => 1,
+            PatternKind::Striped => 2,
+        });
+        self.u8((pattern.space == PatternSpace::Uv).into());
+        self.floats(&pattern.color);
+        self.f32(pattern.scale);
+        self.f32(pattern.width);
+    }
+
+    fn material(&mut self, material: &Materail) {
+        match material {
+            Materail::Phong(phong) => {
+                self.u8(0);
+                self.floats(&phong.base_color);
+                self.f32(phong.specular_factor);
+                self.texture(&phong.diffuse_texture);
+                self.texture(&phong.specular_texture);
+                self.pattern(&phong.pattern);
+            }
+            Materail::Pbr(pbr) => {
+                self.u8(1);
+                self.f32(pbr.ao);
+                self.floats(&pbr.base_color);
+                self.f32(pbr.alpha);
+                self.f32(pbr.roughness);
+                self.f32(pbr.metallic_factor);
+                self.floats(&pbr.emissive);
+                self.texture(&pbr.base_texture);
+                self.texture(&pbr.metallic_texture);
+                self.texture(&pbr.normal_texture);
+                self.f32(pbr.normal_scale);
+                self.texture(&pbr.occlusion_texture);
+                self.f32(pbr.occlusion_strength);
+                self.texture(&pbr.emissive_texture);
+                for set in pbr.uv_sets {
+                    self.u32(set);
+                }
+                self.u8(match pbr.alpha_mode {
+                    AlphaMode::Opaque => 0,
+                    AlphaMode::Mask => 1,
+                    AlphaMode::Blend => 2,
+                });
+                self.f32(pbr.alpha_cutoff);
+                self.u8(pbr.double_sided.into());
+                self.pattern(&pbr.pattern);
+            }
+        }
+    }
+
+    fn skeleton(&mut self, skeleton: &Skeleton) {
+        let pose = &skeleton.rest_pose;
+        self.len(pose.joints.len());
+        for (i, joint) in pose.joints.iter().enumerate() {
+            self.str(
+                skeleton
+                    .joint_names
+                    .get(i)
+                    .map(|n| n.as_str())
+                    .unwrap_or(""),
+            );
+            self.i32(pose.parents[i]);
+            self.transform(joint);
+        }
+
+        self.len(skeleton.skins.len());
+        for skin in skeleton.skins.iter() {
+            self.str(&skin.name);
+            self.len(skin.joints.len());
+            for joint in skin.joints.iter() {
+                self.len(*joint);
+            }
+            self.len(skin.inverse_bind_mats.len());
+            for mat in skin.inverse_bind_mats.iter() {
+                for row in mat.data.iter() {
+                    self.floats(row);
+                }
+            }
+        }
+    }
+
+    fn track<const N: usize>(&mut self, track: &Track<N>) {
+        self.u8(match track.interpolation {
+            Interpolation::Constant => 0,
+            Interpolation::Linear => 1,
+            Interpolation::Cubic => 2,
+        });
+        self.len(track.frames.len());
+        for frame in track.frames.iter() {
+            self.f32(frame.time);
+            self.floats(&frame.m_value);
+            self.floats(&frame.m_in);
+            self.floats(&frame.m_out);
+        }
+    }
+
+    fn clip(&mut self, clip: &Clip) {
+        self.str(&clip.name);
+
+        self.len(clip.tracks.len());
+        for track in clip.tracks.iter() {
+            self.u32(track.id);
+            self.track(&track.position);
+            self.track(&track.rotation);
+            self.track(&track.scaling);
+        }
+
+        self.len(clip.morph_tracks.len());
+        for track in clip.morph_tracks.iter() {
+            self.u32(track.id);
+            self.len(track.weights.len());
+            for weights in track.weights.iter() {
+                self.track(weights);
+            }
+        }
+    }
+}
+
+//___________________________________________________________________________________________________
+// reading
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], LoadError> {
+        let end = self
+            .at
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| LoadError::Invalid(String::from("model cache ends too early")))?;
+
+        let out = &self.bytes[self.at..end];
+        self.at = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.take(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, LoadError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn i32(&mut self) -> Result<i32, LoadError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn f32(&mut self) -> Result<f32, LoadError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn floats<const N: usize>(&mut self) -> Result<[f32; N], LoadError> {
+        let mut out = [0.0; N];
+        for v in out.iter_mut() {
+            *v = self.f32()?;
+        }
+        Ok(out)
+    }
+    fn len(&mut self) -> Result<usize, LoadError> {
+        Ok(self.u32()? as usize)
+    }
+    fn str(&mut self) -> Result<String, LoadError> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| LoadError::Invalid(String::from("model cache has a broken name")))
+    }
+    fn optional(&mut self) -> Result<Option<usize>, LoadError> {
+        let v = self.u32()?;
+        Ok((v != NONE).then_some(v as usize))
+    }
+    /// bulk copy of a block written from memory
+    fn block<T: Plain>(&mut self) -> Result<Vec<T>, LoadError> {
+        let count = self.len()?;
+        let size = count
+            .checked_mul(size_of::<T>())
+            .ok_or_else(|| LoadError::Invalid(String::from("model cache block too big")))?;
+        let bytes = self.take(size)?;
+
+        let mut out: Vec<T> = Vec::with_capacity(count);
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), out.as_mut_ptr() as *mut u8, size);
+            out.set_len(count);
+        }
+        Ok(out)
+    }
+
+    fn transform(&mut self) -> Result<Transform, LoadError> {
+        let translation = Vec3::from(&self.floats()?);
+        let scaling = Vec3::from(&self.floats()?);
+        let orientation = Quat::from(&self.floats()?);
+        Ok(Transform::new(scaling, translation, orientation))
+    }
+
+    fn model(&mut self, model: &mut Model) -> Result<(), LoadError> {
+        let textures = self.textures()?;
+
+        for _ in 0..self.len()? {
+            let mesh = self.mesh(&textures)?;
+            model.meshes.push(mesh);
+        }
+        self.skeleton(&mut model.skeleton)?;
+        for _ in 0..self.len()? {
+            let clip = self.clip()?;
+            model.animations.push(clip);
+        }
+
+        let joints = model.skeleton.rest_pose.joints.len();
+        let tracks = model.animations.iter().flat_map(|clip| clip.tracks.iter());
+        if tracks.into_iter().any(|track| track.id as usize >= joints) {
+            return Err(LoadError::Invalid(String::from(
+                "model cache animates a joint the skeleton doesn't have",
+            )));
+        }
+
+        model.textures.extend(textures);
+        Ok(())
+    }
+
+    fn textures(&mut self) -> Result<Vec<Texture>, LoadError> {
+        let mut textures = Vec::new();
+
+        for _ in 0..self.len()? {
+            let width = self.u32()?;
+            let height = self.u32()?;
+            let pixels: Vec<u8> = self.block()?;
+            let sampling = Sampling {
+                wrap_s: self.u32()?,
+                wrap_t: self.u32()?,
+                min_filter: self.u32()?,
+                mag_filter: self.u32()?,
+            };
+            if pixels.len() != width as usize * height as usize * 4 {
+                return Err(LoadError::Invalid(String::from(
+                    "model cache texture size doesn't match its pixels",
+                )));
+            }
+
+            let mut texture = Texture::new();
+            texture
+                .load_pixels(width, height, gl::RGBA, gl::UNSIGNED_BYTE, &pixels)
+                .map_err(LoadError::Invalid)?;
+            texture.set_sampling(&sampling);
+            textures.push(texture);
+        }
+
+        Ok(textures)
+    }
+
+    fn texture(&mut self, textures: &[Texture]) -> Result<Option<Texture>, LoadError> {
+        match self.optional()? {
+            Some(i) => match textures.get(i) {
+                Some(texture) => Ok(Some(texture.clone())),
+                None => Err(LoadError::Invalid(format!(
+                    "model cache points at texture {i} of {}",
+                    textures.len()
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn mesh(&mut self, textures: &[Texture]) -> Result<Mesh, LoadError> {
+        let mut mesh = Mesh::default();
+        mesh.vbo.data = self.block()?;
+        if self.u8()? == 1 {
+            let mut ebo = EBO::default();
+            ebo.data = self.block()?;
+            if ebo.data.iter().any(|i| *i as usize >= mesh.vbo.data.len()) {
+                return Err(LoadError::Invalid(String::from(
+                    "model cache indexes past its vertices",
+                )));
+            }
+            mesh.ebo = Some(ebo);
+        }
+
+        mesh.texture = self.texture(textures)?;
+        mesh.material = self.material(textures)?;
+        mesh.skin = self.optional()?;
+
+        mesh.instances.clear();
+        for _ in 0..self.len()? {
+            let instance = self.transform()?;
+            mesh.instances.push(instance);
+        }
+        for _ in 0..self.len()? {
+            let node = self.len()?;
+            mesh.nodes.push(node);
+        }
+
+        if self.u8()? == 1 {
+            let mut targets = Vec::new();
+            for _ in 0..self.len()? {
+                targets.push(MorphTarget {
+                    positions: self.block()?,
+                    normals: self.block()?,
+                });
+            }
+            let weights: Vec<f32> = self.block()?;
+            let node = self.len()?;
+
+            let mut morph = Morph::new(targets, weights.clone(), node, mesh.vbo.data.clone());
+            morph.apply(&weights, &mut mesh.vbo.data);
+            mesh.morph = Some(morph);
+        }
+
+        mesh.create();
+        Ok(mesh)
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, LoadError> {
+        Ok(Pattern {
+            kind: match self.u8()? {
+                1 => PatternKind::Checkered,
+                2 => PatternKind::Striped,
+                _ => PatternKind::None,
+            },
+            space: match self.u8()? {
+                1 => PatternSpace::Uv,
+                _ => PatternSpace::Object,
+            },
+            color: self.floats()?,
+            scale: self.f32()?,
+            width: self.f32()?,
+        })
+    }
+
+    fn material(&mut self, textures: &[Texture]) -> Result<Materail, LoadError> {
+        if self.u8()? == 0 {
+            return Ok(Materail::Phong(Phong {
+                base_color: self.floats()?,
+                specular_factor: self.f32()?,
+                diffuse_texture: self.texture(textures)?,
+                specular_texture: self.texture(textures)?,
+                pattern: self.pattern()?,
+            }));
+        }
+
+        Ok(Materail::Pbr(Pbr {
+            ao: self.f32()?,
+            base_color: self.floats()?,
+            alpha: self.f32()?,
+            roughness: self.f32()?,
+            metallic_factor: self.f32()?,
+            emissive: self.floats()?,
+            base_texture: self.texture(textures)?,
+            metallic_texture: self.texture(textures)?,
+            normal_texture: self.texture(textures)?,
+            normal_scale: self.f32()?,
+            occlusion_texture: self.texture(textures)?,
+            occlusion_strength: self.f32()?,
+            emissive_texture: self.texture(textures)?,
+            uv_sets: [
+                self.u32()?,
+                self.u32()?,
+                self.u32()?,
+                self.u32()?,
+                self.u32()?,
+            ],
+            alpha_mode: match self.u8()? {
+                1 => AlphaMode::Mask,
+                2 => AlphaMode::Blend,
+                _ => AlphaMode::Opaque,
+            },
+            alpha_cutoff: self.f32()?,
+            double_sided: self.u8()? == 1,
+            pattern: self.pattern()?,
+        }))
+    }
+
+    fn skeleton(&mut self, skeleton: &mut Skeleton) -> Result<(), LoadError> {
+        let mut pose = Pose::new();
+        for _ in 0..self.len()? {
+            skeleton.joint_names.push(self.str()?);
+            pose.parents.push(self.i32()?);
+            pose.joints.push(self.transform()?);
+        }
+        if pose.parents.iter().any(|p| *p >= pose.joints.len() as i32) {
+            return Err(LoadError::Invalid(String::from(
+                "model cache has a joint with a missing parent",
+            )));
+        }
+        skeleton.rest_pose = pose;
+
+        for _ in 0..self.len()? {
+            let name = self.str()?;
+            let mut joints = Vec::new();
+            for _ in 0..self.len()? {
+                joints.push(self.len()?);
+            }
+            let mut inverse_bind_mats = Vec::new();
+            for _ in 0..self.len()? {
+                let rows = [
+                    self.floats()?,
+                    self.floats()?,
+                    self.floats()?,
+                    self.floats()?,
+                ];
+                inverse_bind_mats.push(Mat4::from(&rows));
+            }
+
+            // skinning looks joints up in the pose and pairs them with their matrices
+            if joints.iter().any(|j| *j >= skeleton.rest_pose.joints.len()) {
+                return Err(LoadError::Invalid(format!(
+                    "model cache skin \"{name}\" uses a joint the skeleton doesn't have"
+                )));
+            }
+            if inverse_bind_mats.len() != joints.len() {
+                return Err(LoadError::Invalid(format!(
+                    "model cache skin \"{name}\" has {} joints but {} inverse bind matrices",
+                    joints.len(),
+                    inverse_bind_mats.len()
+                )));
+            }
+
+            skeleton.skins.push(Skin {
+                name,
+                joints,
+                inverse_bind_mats,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn track<const N: usize>(&mut self) -> Result<Track<N>, LoadError> {
+        let mut track = Track::new();
+        track.interpolation = match self.u8()? {
+            0 => Interpolation::Constant,
+            1 => Interpolation::Linear,
+            _ => Interpolation::Cubic,
+        };
+
+        for _ in 0..self.len()? {
+            let mut frame = Frame::new();
+            frame.time = self.f32()?;
+            frame.m_value = self.floats()?;
+            frame.m_in = self.floats()?;
+            frame.m_out = self.floats()?;
+            track.frames.push(frame);
+        }
+
+        Ok(track)
+    }
+
+    fn clip(&mut self) -> Result<Clip, LoadError> {
+        let mut clip = Clip::new();
+        clip.name = self.str()?;
+
+        for _ in 0..self.len()? {
+            let mut track = TransformTrack::new();
+            track.id = self.u32()?;
+            track.position = self.track()?;
+            track.rotation = self.track()?;
+            track.scaling = self.track()?;
+            clip.tracks.push(track);
+        }
+
+        for _ in 0..self.len()? {
+            let mut track = MorphTrack::new();
+            track.id = self.u32()?;
+            for _ in 0..self.len()? {
+                let weights = self.track()?;
+                track.weights.push(weights);
+            }
+            clip.morph_tracks.push(track);
+        }
+
+        clip.re_calculate_duration();
+        Ok(clip)
+    }
+}
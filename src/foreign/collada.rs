@@ -0,0 +1,481 @@
+use crate::src::animation::{
+    clip::Clip,
+    curves::Interpolation,
+    frame::Frame,
+    pose::Pose,
+    skeleton::{Skeleton, Skin},
+    track_transform::TransformTrack,
+};
+use crate::src::math::{mat4::*, transform::Transform};
+use crate::src::renderer::{
+    buffer::*,
+    material::{Materail, Phong},
+    mesh::*,
+    model::Model,
+    texture::Texture,
+    vertex::Vertex,
+};
+
+use super::error::LoadError;
+
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+// collada(.dae) loader
+// the collada crate does the geometry, skeleton and animation parsing,
+// materials are read straight from the xml since the crate panics on anything but plain colors
+// limitations of the crate carry over:
+// - only the first skeleton is used, every skinned mesh binds to it
+// - node transforms of static meshes and the bind shape matrix are ignored
+// - files are taken as y up
+extern crate collada;
+extern crate xml;
+
+use collada::document::ColladaDocument;
+
+/// surface description of a collada effect
+#[derive(Clone)]
+struct Effect {
+    diffuse: [f32; 3],
+    /// image file relative to the dae
+    texture: Option<String>,
+    shininess: f32,
+}
+
+pub struct Collada {
+    /// folder the file sits in, textures are looked up relative to it
+    folder: PathBuf,
+    document: ColladaDocument,
+}
+
+impl Collada {
+    pub fn new(path: &Path) -> Result<Collada, LoadError> {
+        if !path.is_file() {
+            return Err(LoadError::NotAFile(path.to_path_buf()));
+        }
+
+        let document =
+            ColladaDocument::from_path(path).map_err(|e| LoadError::Collada(e.to_string()))?;
+
+        Ok(Collada {
+            folder: path.parent().unwrap_or(Path::new("")).to_path_buf(),
+            document,
+        })
+    }
+
+    /// textures referenced by the document get looked up relative to folder
+    pub fn from_str(src: &str, folder: &Path) -> Result<Collada, LoadError> {
+        let document =
+            ColladaDocument::from_str(src).map_err(|e| LoadError::Collada(e.to_string()))?;
+
+        Ok(Collada {
+            folder: folder.to_path_buf(),
+            document,
+        })
+    }
+
+    //_______________________________________________________________________________________________
+    pub fn populate_model(&self, model: &mut Model) -> Result<(), LoadError> {
+        self.extract_skeleton(&mut model.skeleton)?;
+        self.extract_meshes_and_textures(&mut model.meshes, &mut model.textures)?;
+        self.extract_animations(&mut model.skeleton, &mut model.animations);
+
+        Ok(())
+    }
+
+    /// the crate panics on a lot of malformed input instead of returning errors
+    fn guard<T>(what: &str, f: impl FnOnce() -> T) -> Result<T, LoadError> {
+        panic::catch_unwind(AssertUnwindSafe(f))
+            .map_err(|_| LoadError::Collada(format!("malformed {what}")))
+    }
+
+    fn ns(&self) -> Option<&str> {
+        self.document.root_element.ns.as_deref()
+    }
+
+    //_______________________________________________________________________________________________
+    // skeleton
+
+    /// collada matrices are stored row after row, same as Mat4
+    fn to_mat(mat: &[[f32; 4]; 4]) -> Mat4 {
+        Mat4::from(mat)
+    }
+
+    fn extract_skeleton(&self, skeleton: &mut Skeleton) -> Result<(), LoadError> {
+        let skeletons = Self::guard("skeleton", || self.document.get_skeletons())?;
+        let Some(source) = skeletons.and_then(|s| s.into_iter().next()) else {
+            return Ok(());
+        };
+
+        let mut pose = Pose::new();
+        pose.resize(source.joints.len());
+
+        for (i, joint) in source.joints.iter().enumerate() {
+            pose.joints[i] = Transform::from_mat(&Self::to_mat(&source.bind_poses[i]));
+            pose.parents[i] = if joint.is_root() {
+                -1
+            } else {
+                joint.parent_index as i32
+            };
+            skeleton.joint_names.push(joint.name.clone());
+        }
+        skeleton.rest_pose = pose;
+
+        // vertex weights index the skeletons joints directly
+        skeleton.skins.push(Skin {
+            name: String::from("skin"),
+            joints: (0..source.joints.len()).collect(),
+            inverse_bind_mats: source
+                .joints
+                .iter()
+                .map(|joint| Self::to_mat(&joint.inverse_bind_pose))
+                .collect(),
+        });
+
+        Ok(())
+    }
+
+    //_______________________________________________________________________________________________
+    // meshes
+
+    pub fn extract_meshes_and_textures(
+        &self,
+        meshes: &mut Vec<Mesh>,
+        textures: &mut Vec<Texture>,
+    ) -> Result<(), LoadError> {
+        let obj_set = Self::guard("geometry", || self.document.get_obj_set())?;
+        let Some(obj_set) = obj_set else {
+            return Ok(());
+        };
+
+        let materials = self.extract_materials();
+        // textures get shared between meshes, keyed by file
+        let mut loaded: HashMap<String, Texture> = HashMap::new();
+
+        for object in obj_set.objects.iter() {
+            for geometry in object.geometry.iter() {
+                for element in geometry.mesh.iter() {
+                    let (corners, material) = match element {
+                        collada::PrimitiveElement::Triangles(triangles) => {
+                            (Self::triangle_corners(triangles), &triangles.material)
+                        }
+                        collada::PrimitiveElement::Polylist(polylist) => {
+                            (Self::polylist_corners(polylist), &polylist.material)
+                        }
+                    };
+
+                    let effect = material.as_ref().and_then(|name| materials.get(name));
+                    let mut mesh = Self::build_mesh(object, &corners, effect)?;
+
+                    if let Some(file) = effect.and_then(|e| e.texture.as_ref()) {
+                        if !loaded.contains_key(file) {
+                            let mut tex = Texture::new();
+                            match tex.from(&self.folder.join(file)) {
+                                Ok(()) => {
+                                    textures.push(tex.clone());
+                                    loaded.insert(file.clone(), tex);
+                                }
+                                Err(e) => println!("couldn't load texture {file}: {e}"),
+                            }
+                        }
+                        mesh.texture = loaded.get(file).cloned();
+                        if let Materail::Phong(phong) = &mut mesh.material {
+                            phong.diffuse_texture = mesh.texture.clone();
+                        }
+                    }
+
+                    mesh.create();
+                    meshes.push(mesh);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// (position, texcoord, normal) indices of every triangle corner
+    fn triangle_corners(triangles: &collada::Triangles) -> Vec<collada::VTNIndex> {
+        let mut corners = Vec::new();
+
+        for (i, tri) in triangles.vertices.iter().enumerate() {
+            let tex = triangles.tex_vertices.as_ref().and_then(|t| t.get(i));
+            let norm = triangles.normals.as_ref().and_then(|n| n.get(i));
+
+            corners.push((tri.0, tex.map(|t| t.0), norm.map(|n| n.0)));
+            corners.push((tri.1, tex.map(|t| t.1), norm.map(|n| n.1)));
+            corners.push((tri.2, tex.map(|t| t.2), norm.map(|n| n.2)));
+        }
+
+        corners
+    }
+
+    /// polygons come already split into triangles, points and lines get skipped
+    fn polylist_corners(polylist: &collada::Polylist) -> Vec<collada::VTNIndex> {
+        let mut corners = Vec::new();
+
+        for shape in polylist.shapes.iter() {
+            if let collada::Shape::Triangle(a, b, c) = shape {
+                corners.extend_from_slice(&[*a, *b, *c]);
+            }
+        }
+
+        corners
+    }
+
+    /// corners sharing all their indices share a vertex
+    fn build_mesh(
+        object: &collada::Object,
+        corners: &[collada::VTNIndex],
+        effect: Option<&Effect>,
+    ) -> Result<Mesh, LoadError> {
+        let mut mesh = Mesh::default();
+        let mut ebo = EBO::default();
+        let mut lookup: HashMap<collada::VTNIndex, u32> = HashMap::new();
+
+        let color = effect.map(|e| e.diffuse).unwrap_or([1.0; 3]);
+        let skinned = !object.joint_weights.is_empty();
+
+        for corner in corners {
+            if let Some(index) = lookup.get(corner) {
+                ebo.data.push(*index);
+                continue;
+            }
+
+            let (v, t, n) = *corner;
+            let Some(pos) = object.vertices.get(v) else {
+                return Err(LoadError::Invalid(format!(
+                    "{} indexes past its {} vertices",
+                    object.name,
+                    object.vertices.len()
+                )));
+            };
+
+            let mut vert = Vertex {
+                pos: [pos.x as f32, pos.y as f32, pos.z as f32],
+                col: color,
+                ..Vertex::DEFAULT
+            };
+            if let Some(norm) = n.and_then(|n| object.normals.get(n)) {
+                vert.norm = [norm.x as f32, norm.y as f32, norm.z as f32];
+            }
+            if let Some(tex) = t.and_then(|t| object.tex_vertices.get(t)) {
+                vert.tex = [tex.x as f32, tex.y as f32];
+            }
+            if let Some(weights) = object.joint_weights.get(v) {
+                vert.weights = weights.weights;
+                vert.bone_ids = weights.joints.map(|j| j as i32);
+            }
+
+            let index = mesh.vbo.data.len() as u32;
+            mesh.vbo.data.push(vert);
+            lookup.insert(*corner, index);
+            ebo.data.push(index);
+        }
+
+        mesh.ebo = Some(ebo);
+        mesh.skin = skinned.then_some(0);
+        mesh.material = Materail::Phong(Phong {
+            base_color: color,
+            specular_factor: effect
+                .map(|e| e.shininess)
+                .filter(|s| *s > 0.0)
+                .unwrap_or(32.0),
+            ..Phong::default()
+        });
+
+        Ok(mesh)
+    }
+
+    //_______________________________________________________________________________________________
+    // materials
+    // material -> effect -> diffuse color or texture sampler -> surface -> image -> file
+
+    /// effects keyed by the material ids primitives refer to
+    fn extract_materials(&self) -> HashMap<String, Effect> {
+        let ns = self.ns();
+        let root = &self.document.root_element;
+
+        let images: HashMap<String, String> = root
+            .get_child("library_images", ns)
+            .map(|lib| {
+                lib.get_children("image", ns)
+                    .filter_map(|image| {
+                        let id = image.get_attribute("id", None)?;
+                        let init = image.get_child("init_from", ns)?;
+                        // collada 1.5 nests the file in a ref element
+                        let file = match init.get_child("ref", ns) {
+                            Some(file) => file.content_str(),
+                            None => init.content_str(),
+                        };
+                        Some((id.to_string(), file.trim().replace("%20", " ")))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut effects: HashMap<String, Effect> = HashMap::new();
+        if let Some(lib) = root.get_child("library_effects", ns) {
+            for effect in lib.get_children("effect", ns) {
+                if let Some(id) = effect.get_attribute("id", None) {
+                    effects.insert(id.to_string(), self.read_effect(effect, &images));
+                }
+            }
+        }
+
+        let mut materials = HashMap::new();
+        if let Some(lib) = root.get_child("library_materials", ns) {
+            for material in lib.get_children("material", ns) {
+                let id = material.get_attribute("id", None);
+                let url = material
+                    .get_child("instance_effect", ns)
+                    .and_then(|instance| instance.get_attribute("url", None));
+
+                if let (Some(id), Some(url)) = (id, url) {
+                    if let Some(effect) = effects.get(url.trim_start_matches('#')) {
+                        materials.insert(id.to_string(), effect.clone());
+                    }
+                }
+            }
+        }
+
+        materials
+    }
+
+    fn read_effect(&self, effect: &xml::Element, images: &HashMap<String, String>) -> Effect {
+        let ns = self.ns();
+        let mut out = Effect {
+            diffuse: [1.0; 3],
+            texture: None,
+            shininess: 0.0,
+        };
+
+        let Some(profile) = effect.get_child("profile_COMMON", ns) else {
+            return out;
+        };
+        let Some(technique) = profile.get_child("technique", ns) else {
+            return out;
+        };
+        let shading = ["phong", "blinn", "lambert", "constant"]
+            .iter()
+            .find_map(|name| technique.get_child(name, ns));
+        let Some(shading) = shading else {
+            return out;
+        };
+
+        if let Some(diffuse) = shading.get_child("diffuse", ns) {
+            if let Some(color) = diffuse.get_child("color", ns) {
+                let values: Vec<f32> = color
+                    .content_str()
+                    .split_whitespace()
+                    .filter_map(|v| v.parse().ok())
+                    .collect();
+                if values.len() >= 3 {
+                    out.diffuse = [values[0], values[1], values[2]];
+                }
+            }
+            if let Some(texture) = diffuse.get_child("texture", ns) {
+                out.texture = texture
+                    .get_attribute("texture", None)
+                    .and_then(|sampler| Self::resolve_sampler(profile, sampler, ns))
+                    .and_then(|image| images.get(&image).cloned());
+            }
+        }
+
+        if let Some(shininess) = shading
+            .get_child("shininess", ns)
+            .and_then(|s| s.get_child("float", ns))
+        {
+            out.shininess = shininess.content_str().trim().parse().unwrap_or(0.0);
+        }
+
+        out
+    }
+
+    /// follow a sampler through the effects params down to an image id
+    /// some exporters skip the params and point at the image straight away
+    fn resolve_sampler(profile: &xml::Element, sid: &str, ns: Option<&str>) -> Option<String> {
+        let param = |sid: &str| {
+            profile
+                .get_children("newparam", ns)
+                .find(|p| p.get_attribute("sid", None) == Some(sid))
+        };
+
+        let Some(sampler) = param(sid) else {
+            return Some(sid.to_string());
+        };
+
+        let surface_sid = sampler
+            .get_child("sampler2D", ns)?
+            .get_child("source", ns)?
+            .content_str();
+
+        let image = param(surface_sid.trim())?
+            .get_child("surface", ns)?
+            .get_child("init_from", ns)?
+            .content_str();
+
+        Some(image.trim().to_string())
+    }
+
+    //_______________________________________________________________________________________________
+    // animations
+    // every node animation becomes a track of a single clip since collada has no notion of clips
+
+    fn extract_animations(&self, skeleton: &mut Skeleton, clips: &mut Vec<Clip>) {
+        let animations = match Self::guard("animations", || self.document.get_animations()) {
+            Ok(Some(animations)) => animations,
+            Ok(None) => return,
+            Err(e) => {
+                println!("skipping animations: {e}");
+                return;
+            }
+        };
+
+        let mut clip = Clip::new();
+        clip.name = String::from("default");
+
+        for animation in animations.iter() {
+            // targets look like "joint_id/transform"
+            let node = animation.target.split('/').next().unwrap_or("");
+            let Some(joint) = skeleton.joint_names.iter().position(|name| name == node) else {
+                continue;
+            };
+
+            let mut track = TransformTrack::new();
+            track.id = joint as u32;
+            // keys are baked matrices without tangents
+            track.position.interpolation = Interpolation::Linear;
+            track.rotation.interpolation = Interpolation::Linear;
+            track.scaling.interpolation = Interpolation::Linear;
+
+            for (time, mat) in animation.sample_times.iter().zip(&animation.sample_poses) {
+                let transform = Transform::from_mat(&Self::to_mat(mat));
+
+                let mut position = Frame::new();
+                position.time = *time;
+                position.m_value = transform.translation.to_array();
+                track.position.frames.push(position);
+
+                let mut rotation = Frame::new();
+                rotation.time = *time;
+                rotation.m_value = transform.orientation.to_array();
+                track.rotation.frames.push(rotation);
+
+                let mut scaling = Frame::new();
+                scaling.time = *time;
+                scaling.m_value = transform.scaling.to_array();
+                track.scaling.frames.push(scaling);
+            }
+
+            clip.tracks.push(track);
+        }
+
+        if clip.tracks.is_empty() {
+            return;
+        }
+
+        clip.re_calculate_duration();
+        clips.push(clip);
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// everything that can go wrong bringing a foreign file into the engine
+#[derive(Debug)]
+pub enum LoadError {
+    /// loaders want the file itself, not the folder its in
+    NotAFile(PathBuf),
+    /// the file couldn't be read
+    Io(PathBuf, std::io::Error),
+    /// the glTF crate couldn't read or parse the file(includes io errors)
+    Gltf(gltf::Error),
+    /// the collada crate couldn't read or parse the file
+    Collada(String),
+    /// no loader handles the files extension
+    UnknownFormat(PathBuf),
+    /// model cache written with another version of the format, holds the version it has
+    Outdated(u32),
+    /// the file parsed but its contents don't make sense
+    Invalid(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAFile(path) => write!(f, "\"{}\" is not a file", path.display()),
+            Self::Io(path, e) => write!(f, "{}: {e}", path.display()),
+            Self::Gltf(e) => write!(f, "{e}"),
+            Self::Collada(message) => write!(f, "{message}"),
+            Self::UnknownFormat(path) => {
+                write!(f, "don't know how to load \"{}\"", path.display())
+            }
+            Self::Outdated(version) => write!(
+                f,
+                "model cache is version {version}, expected {}, convert it again",
+                super::cache::VERSION
+            ),
+            Self::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<gltf::Error> for LoadError {
+    fn from(e: gltf::Error) -> Self {
+        Self::Gltf(e)
+    }
+}
@@ -1,213 +1,575 @@
 use crate::src::animation::{
     clip::Clip,
     curves::Interpolation,
-    frame::{QuaternionFrame, VectorFrame},
+    frame::Frame,
     pose::Pose,
-    skeleton::Skeleton,
+    skeleton::{Skeleton, Skin},
+    track::ScalarTrack,
+    track_morph::MorphTrack,
     track_transform::TransformTrack,
 };
+use crate::src::math::misc::degrees;
 use crate::src::math::{mat4::*, quaternion::*, transform::Transform, vec3::*};
-use crate::src::renderer::{buffer::*, mesh::*, model::Model, texture::Texture, vertex::Vertex};
+use crate::src::renderer::{
+    buffer::*,
+    material::{AlphaMode, Materail, Pbr},
+    mesh::*,
+    model::Model,
+    morph::{Morph, MorphTarget},
+    texture::{Sampling, Texture},
+    vertex::Vertex,
+};
+
+use crate::src::scene::{
+    camera::{Camera, Projection},
+    lights::{DirectionalLight, PointLight, SpotLight},
+};
+
+use super::error::LoadError;
 
-use std::fs;
 use std::path::Path;
 // gltf loader definations
 // not perfect but works well enough for most files
 // still a work in progress
 extern crate gltf;
 
+/// where a glTF mesh ends up in the model
+#[derive(Clone, Default)]
+struct MeshPlacement {
+    instances: Vec<Transform>,
+    /// node of each instance
+    nodes: Vec<usize>,
+    skin: Option<usize>,
+    /// first node using the mesh, its the one driving the morph target weights
+    node: Option<usize>,
+    /// morph weights the node overrides the meshes defaults with
+    weights: Option<Vec<f32>>,
+}
+
 pub struct Gltf {
-    parent_folder: String,
     document: gltf::Document,
     buffers: Vec<gltf::buffer::Data>,
+    /// decoded pixels of every image, whether it came from a file, a data uri or a glb buffer
+    images: Vec<gltf::image::Data>,
 }
 
 impl Gltf {
-    pub fn new(parent: &Path) -> Gltf {
-        let paths = fs::read_dir(parent).unwrap();
+    /// load a .gltf or .glb file, buffers and images it points at are looked up next to it
+    pub fn new(path: &Path) -> Result<Gltf, LoadError> {
+        if !path.is_file() {
+            return Err(LoadError::NotAFile(path.to_path_buf()));
+        }
 
-        let mut gltf_file = String::new();
-        for entry in paths {
-            let path = entry.unwrap().path();
+        let (document, buffers, images) = gltf::import(path)?;
 
-            if let Some(extension) = path.extension() {
-                if extension.eq("gltf") || extension.eq("glb") {
-                    gltf_file = String::from(path.to_str().unwrap());
-                }
-            }
-        }
+        Ok(Gltf {
+            document,
+            buffers,
+            images,
+        })
+    }
 
-        let (document, buffers, _) = gltf::import(gltf_file).unwrap();
+    /// load a file already in memory, it has to be self contained(glb or data uris only)
+    pub fn from_slice(bytes: &[u8]) -> Result<Gltf, LoadError> {
+        let (document, buffers, images) = gltf::import_slice(bytes)?;
 
-        let parent_folder = String::from(parent.to_str().unwrap());
-        Gltf {
-            parent_folder,
+        Ok(Gltf {
             document,
             buffers,
-        }
+            images,
+        })
     }
 
     //_______________________________________________________________________________________________
-    pub fn populate_model(&self, model: &mut Model) {
-        self.extract_meshes_and_textures(&mut model.meshes, &mut model.textures);
-        self.extract_skeleton(&mut model.skeleton);
+    pub fn populate_model(&self, model: &mut Model) -> Result<(), LoadError> {
+        self.extract_meshes_and_textures(&mut model.meshes, &mut model.textures)?;
+        self.extract_skeleton(&mut model.skeleton)?;
         self.extract_animations(&mut model.animations);
+
+        Ok(())
     }
 
-    fn extract_skeleton(&self, skeleton: &mut Skeleton) {
-        self.extract_inverse_bind_mats(&mut skeleton.inverse_bind_pose);
+    fn extract_skeleton(&self, skeleton: &mut Skeleton) -> Result<(), LoadError> {
+        self.extract_skins(&mut skeleton.skins)?;
         self.extract_rest_pose(&mut skeleton.rest_pose);
         self.extract_joint_names(&mut skeleton.joint_names);
+
+        Ok(())
     }
 
     //_______________________________________________________________________________________________
 
-    pub fn extract_meshes_and_textures(&self, meshes: &mut Vec<Mesh>, textures: &mut Vec<Texture>) {
-        let mut skins = Vec::new();
-        skins.resize(self.document.skins().count(), Vec::new());
-
-        self.document.skins().for_each(|skin| {
-            let mut joints = Vec::new();
-            skin.joints().for_each(|joint| {
-                joints.push(joint.index() as i32);
-            });
-            skins[0] = joints;
-        });
-
+    pub fn extract_meshes_and_textures(
+        &self,
+        meshes: &mut Vec<Mesh>,
+        textures: &mut Vec<Texture>,
+    ) -> Result<(), LoadError> {
         let mut texture_ids = Vec::new();
+        let placements = self.extract_mesh_placements();
 
-        self.document.meshes().for_each(|mesh| {
-            let primitives = mesh.primitives();
-            //assuming it only contains one skin
-            let ids = &skins[0];
+        for gltf_mesh in self.document.meshes() {
+            // meshes no node in the scene points at dont get drawn
+            let placement = &placements[gltf_mesh.index()];
+            if placement.instances.is_empty() {
+                continue;
+            }
 
-            primitives.for_each(|primitive| {
+            for primitive in gltf_mesh.primitives() {
                 //prepare for next batch of data
                 let mut mesh = Mesh::default();
+                mesh.instances = placement.instances.clone();
+                mesh.nodes = placement.nodes.clone();
+                mesh.skin = placement.skin;
 
-                let pbr_info = &primitive.material().pbr_metallic_roughness();
-                let color = pbr_info.base_color_factor();
-
-                println!("{:?}", primitive.material().emissive_factor());
-                println!("{:?}", pbr_info.metallic_factor());
-                println!("{:?}", pbr_info.roughness_factor());
-
-                // uhhhh...
-                // good enough i guess
-                // should probably change my code architecture to load textures better
-                if let Some(texture) = pbr_info.base_color_texture() {
-                    match texture.texture().source().source() {
-                        gltf::image::Source::Uri { uri, .. } => {
-                            if texture_ids.contains(&texture.texture().index()) {
-                                let index = texture_ids
-                                    .iter()
-                                    .position(|&v| v == texture.texture().index())
-                                    .unwrap();
-
-                                mesh.texture = Some(textures[index].clone());
-                            } else {
-                                let mut tex = Texture::new();
-                                let parent_folder = Path::new(&self.parent_folder[..]);
-
-                                tex.from(parent_folder.join(uri).as_path()).unwrap();
-
-                                mesh.texture = Some(tex.clone());
-
-                                textures.push(tex);
-                                texture_ids.push(texture.texture().index());
-                            }
-                        }
-
-                        _ => {
-                            println!("texture source not surported!")
-                        }
-                    }
-                }
+                mesh.material = Materail::Pbr(self.extract_material(
+                    &primitive.material(),
+                    textures,
+                    &mut texture_ids,
+                ));
 
                 let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
 
-                // extract positions
-                if let Some(positions) = reader.read_positions() {
-                    positions.for_each(|pos| {
-                        mesh.vbo.data.push(Vertex {
-                            pos: pos,
-                            ..Vertex::DEFAULT
-                        });
-                    });
+                // extract positions, the only attribute a primitive can't do without
+                let Some(positions) = reader.read_positions() else {
+                    return Err(LoadError::Invalid(format!(
+                        "primitive {} of mesh {} has no positions",
+                        primitive.index(),
+                        gltf_mesh.index()
+                    )));
                 };
+                mesh.vbo.data = positions
+                    .map(|pos| Vertex {
+                        pos,
+                        // vertex colors multiply the base color, white unless the file has some
+                        col: [1.0; 3],
+                        ..Vertex::DEFAULT
+                    })
+                    .collect();
+
+                // the rest are optional, vertices keep their defaults for whatever is missing
+                let verts = &mut mesh.vbo.data;
 
                 //extract normals
                 if let Some(normals) = reader.read_normals() {
-                    normals.enumerate().for_each(|(i, norm)| {
-                        mesh.vbo.data[i].norm = norm;
-                    });
+                    for (vert, norm) in verts.iter_mut().zip(normals) {
+                        vert.norm = norm;
+                    }
                 }
 
                 //extract colors
                 if let Some(colors) = reader.read_colors(0) {
-                    colors.into_rgb_f32().enumerate().for_each(|(i, color)| {
-                        mesh.vbo.data[i].col = color;
-                    });
+                    for (vert, color) in verts.iter_mut().zip(colors.into_rgb_f32()) {
+                        vert.col = color;
+                    }
                 }
                 //extract texture coordinates
                 if let Some(texels) = reader.read_tex_coords(0) {
-                    texels.into_f32().enumerate().for_each(|(i, texel)| {
-                        mesh.vbo.data[i].tex = texel;
-                    });
+                    for (vert, texel) in verts.iter_mut().zip(texels.into_f32()) {
+                        vert.tex = texel;
+                    }
+                }
+                if let Some(texels) = reader.read_tex_coords(1) {
+                    for (vert, texel) in verts.iter_mut().zip(texels.into_f32()) {
+                        vert.tex1 = texel;
+                    }
                 }
 
                 //extract weights
                 if let Some(weights) = reader.read_weights(0) {
-                    weights.into_f32().enumerate().for_each(|(i, weight)| {
-                        mesh.vbo.data[i].weights = weight;
-                    });
+                    for (vert, weight) in verts.iter_mut().zip(weights.into_f32()) {
+                        vert.weights = weight;
+                    }
                 }
 
                 //extract bone ids
+                //these index the joints of the meshes skin, not the nodes
                 if let Some(boneids) = reader.read_joints(0) {
-                    boneids.into_u16().enumerate().for_each(|(i, batch)| {
-                        if ids.len() > 0 {
-                            mesh.vbo.data[i].bone_ids = [
-                                ids[batch[0] as usize],
-                                ids[batch[1] as usize],
-                                ids[batch[2] as usize],
-                                ids[batch[3] as usize],
-                            ];
-                        } else {
-                            mesh.vbo.data[i].bone_ids = batch.map(|id| id as i32);
-                        }
-                    });
-                }
-
-                // alittle cheating
-                // have to come up with a better way of handling materials
-                // might impliment a pbr system
-                for vert in &mut mesh.vbo.data {
-                    vert.col = [color[0], color[1], color[2]];
+                    for (vert, batch) in verts.iter_mut().zip(boneids.into_u16()) {
+                        vert.bone_ids = batch.map(|id| id as i32);
+                    }
                 }
 
                 //extract indices
                 if let Some(indices) = reader.read_indices() {
-                    mesh.ebo = Some(EBO::default());
-                    mesh.ebo.as_mut().unwrap().data = indices.into_u32().collect();
+                    let indices: Vec<u32> = indices.into_u32().collect();
+                    if indices.iter().any(|&i| i as usize >= verts.len()) {
+                        return Err(LoadError::Invalid(format!(
+                            "primitive {} of mesh {} indexes past its {} vertices",
+                            primitive.index(),
+                            gltf_mesh.index(),
+                            verts.len()
+                        )));
+                    }
+
+                    let mut ebo = EBO::default();
+                    ebo.data = indices;
+                    mesh.ebo = Some(ebo);
                 }
+
+                //extract morph targets, blended with the default weights before uploading
+                let targets: Vec<MorphTarget> = reader
+                    .read_morph_targets()
+                    .map(|(positions, normals, _)| MorphTarget {
+                        positions: positions.map(|p| p.collect()).unwrap_or_default(),
+                        normals: normals.map(|n| n.collect()).unwrap_or_default(),
+                    })
+                    .collect();
+
+                if !targets.is_empty() {
+                    let mut weights = placement
+                        .weights
+                        .clone()
+                        .or_else(|| gltf_mesh.weights().map(|w| w.to_vec()))
+                        .unwrap_or_default();
+                    weights.resize(targets.len(), 0.0);
+
+                    let mut morph = Morph::new(
+                        targets,
+                        weights.clone(),
+                        placement.node.unwrap_or(0),
+                        mesh.vbo.data.clone(),
+                    );
+                    morph.apply(&weights, &mut mesh.vbo.data);
+                    mesh.morph = Some(morph);
+                }
+
                 mesh.create();
 
                 meshes.push(mesh);
-            });
-        });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// walks the node tree of the default scene(first one if none is marked)
+    /// and gives back every node in it along with its global transform  
+    /// None if the file has no scenes at all
+    fn scene_nodes(&self) -> Option<Vec<(gltf::Node<'_>, Transform)>> {
+        let scene = self
+            .document
+            .default_scene()
+            .or_else(|| self.document.scenes().next())?;
+
+        let mut nodes = Vec::new();
+        let mut stack: Vec<(gltf::Node, Transform)> = scene
+            .nodes()
+            .map(|node| (node, Transform::DEFAULT))
+            .collect();
+
+        while let Some((node, parent)) = stack.pop() {
+            let global = Transform::combine(&parent, &Self::get_local_transform(&node));
+
+            node.children()
+                .for_each(|child| stack.push((child, global)));
+            nodes.push((node, global));
+        }
+
+        Some(nodes)
+    }
+
+    /// works out where every mesh gets placed, one transform per node using the mesh  
+    /// skinned meshes follow their joints instead so they only get a single identity transform
+    /// and bind to the skin of the first node that uses them
+    fn extract_mesh_placements(&self) -> Vec<MeshPlacement> {
+        let mut placements = Vec::new();
+        placements.resize(self.document.meshes().count(), MeshPlacement::default());
+
+        // no scenes at all, just draw every mesh where it is
+        let Some(nodes) = self.scene_nodes() else {
+            placements
+                .iter_mut()
+                .for_each(|p| p.instances.push(Transform::DEFAULT));
+            return placements;
+        };
+
+        for (node, global) in nodes {
+            if let Some(mesh) = node.mesh() {
+                let placement = &mut placements[mesh.index()];
+                if placement.node.is_none() {
+                    placement.node = Some(node.index());
+                    placement.weights = node.weights().map(|w| w.to_vec());
+                }
+                if let Some(skin) = node.skin() {
+                    if placement.instances.is_empty() {
+                        placement.instances.push(Transform::DEFAULT);
+                        placement.skin = Some(skin.index());
+                    }
+                } else {
+                    placement.instances.push(global);
+                    placement.nodes.push(node.index());
+                }
+            }
+        }
+
+        placements
+    }
+
+    //_______________________________________________________________________________________________
+    // cameras and lights(KHR_lights_punctual) placed by their nodes
+    // both look down the nodes -z axis
+
+    /// every camera in the default scene, placed relative to the given transform
+    pub fn extract_cameras(&self, place: &Transform) -> Vec<Camera> {
+        let mut cameras = Vec::new();
+
+        for (node, global) in self.scene_nodes().unwrap_or_default() {
+            let Some(gltf_camera) = node.camera() else {
+                continue;
+            };
+            let global = Transform::combine(place, &global);
+
+            let mut camera = Camera::default();
+            camera.pos = global.translation;
+            camera.look_towards(global.orientation * vec3(0.0, 0.0, -1.0));
+
+            match gltf_camera.projection() {
+                gltf::camera::Projection::Perspective(perspective) => {
+                    camera.set_fov(degrees(perspective.yfov()));
+                    camera.near = perspective.znear();
+                    // infinite projections get our usual far plane
+                    if let Some(far) = perspective.zfar() {
+                        camera.far = far;
+                    }
+                }
+                gltf::camera::Projection::Orthographic(orthographic) => {
+                    camera.projection = Projection::Orthographic {
+                        ymag: orthographic.ymag(),
+                    };
+                    camera.near = orthographic.znear();
+                    camera.far = orthographic.zfar();
+                }
+            }
+
+            cameras.push(camera);
+        }
+
+        cameras
+    }
+
+    /// punctual lights in the default scene placed relative to the given transform,
+    /// intensities get folded into the colors
+    pub fn extract_lights(
+        &self,
+        place: &Transform,
+        point_lights: &mut Vec<PointLight>,
+        spot_lights: &mut Vec<SpotLight>,
+        suns: &mut Vec<DirectionalLight>,
+    ) {
+        use gltf::khr_lights_punctual::Kind;
+
+        for (node, global) in self.scene_nodes().unwrap_or_default() {
+            let Some(light) = node.light() else {
+                continue;
+            };
+            let global = Transform::combine(place, &global);
+
+            let pos = global.translation;
+            let dir = global.orientation * vec3(0.0, 0.0, -1.0);
+            let col = Vec3::from(&light.color()) * light.intensity();
+
+            match light.kind() {
+                Kind::Point => point_lights.push(PointLight { pos, col }),
+                Kind::Spot {
+                    inner_cone_angle,
+                    outer_cone_angle,
+                } => spot_lights.push(SpotLight {
+                    pos,
+                    dir,
+                    col,
+                    inner_cone: degrees(inner_cone_angle),
+                    outer_cone: degrees(outer_cone_angle),
+                }),
+                Kind::Directional => {
+                    let mut sun = DirectionalLight::default();
+                    sun.dir = dir;
+                    sun.color = col;
+                    suns.push(sun);
+                }
+            }
+        }
+    }
+
+    /// metallic-roughness material of a primitive, glTF defaults fill in whatever isn't set
+    fn extract_material(
+        &self,
+        material: &gltf::Material,
+        textures: &mut Vec<Texture>,
+        texture_ids: &mut Vec<usize>,
+    ) -> Pbr {
+        let pbr_info = material.pbr_metallic_roughness();
+        let [r, g, b, a] = pbr_info.base_color_factor();
+
+        let mut pbr = Pbr {
+            base_color: [r, g, b],
+            alpha: a,
+            metallic_factor: pbr_info.metallic_factor(),
+            roughness: pbr_info.roughness_factor(),
+            emissive: material.emissive_factor(),
+            alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+            double_sided: material.double_sided(),
+            ..Pbr::default()
+        };
+
+        pbr.alpha_mode = match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        };
+
+        let mut load =
+            |texture: gltf::Texture| self.extract_texture(&texture, textures, texture_ids);
+
+        // uv_sets follows the texture unit order: base, metallic, normal, occlusion, emissive
+        if let Some(info) = pbr_info.base_color_texture() {
+            pbr.base_texture = load(info.texture());
+            pbr.uv_sets[0] = Self::uv_set(info.tex_coord());
+        }
+        if let Some(info) = pbr_info.metallic_roughness_texture() {
+            pbr.metallic_texture = load(info.texture());
+            pbr.uv_sets[1] = Self::uv_set(info.tex_coord());
+        }
+        if let Some(normal) = material.normal_texture() {
+            pbr.normal_texture = load(normal.texture());
+            pbr.normal_scale = normal.scale();
+            pbr.uv_sets[2] = Self::uv_set(normal.tex_coord());
+        }
+        if let Some(occlusion) = material.occlusion_texture() {
+            pbr.occlusion_texture = load(occlusion.texture());
+            pbr.occlusion_strength = occlusion.strength();
+            pbr.uv_sets[3] = Self::uv_set(occlusion.tex_coord());
+        }
+        if let Some(info) = material.emissive_texture() {
+            pbr.emissive_texture = load(info.texture());
+            pbr.uv_sets[4] = Self::uv_set(info.tex_coord());
+        }
+
+        pbr
     }
 
-    pub fn extract_materials() {}
+    /// textures get shared between materials, texture_ids holds the glTF index of each loaded one
+    fn extract_texture(
+        &self,
+        texture: &gltf::Texture,
+        textures: &mut Vec<Texture>,
+        texture_ids: &mut Vec<usize>,
+    ) -> Option<Texture> {
+        if let Some(index) = texture_ids.iter().position(|&id| id == texture.index()) {
+            return Some(textures[index].clone());
+        }
+
+        // gltf::import already decoded the image no matter where it's stored
+        let image = &self.images[texture.source().index()];
+
+        // gl samples red/rg textures as (r,0,0,1)/(r,g,0,1), grayscale(+alpha) gets spread out to rgba
+        use gltf::image::Format;
+        let gray;
+        let (format, data_type, pixels) = match image.format {
+            Format::R8 | Format::R8G8 | Format::R16 | Format::R16G16 => {
+                let (channels, size) = match image.format {
+                    Format::R8 => (1, 1),
+                    Format::R8G8 => (2, 1),
+                    Format::R16 => (1, 2),
+                    _ => (2, 2),
+                };
+                gray = Self::gray_to_rgba(&image.pixels, channels, size);
+                let data_type = if size == 1 {
+                    gl::UNSIGNED_BYTE
+                } else {
+                    gl::UNSIGNED_SHORT
+                };
+                (gl::RGBA, data_type, gray.as_slice())
+            }
+            Format::R8G8B8 => (gl::RGB, gl::UNSIGNED_BYTE, image.pixels.as_slice()),
+            Format::R8G8B8A8 => (gl::RGBA, gl::UNSIGNED_BYTE, image.pixels.as_slice()),
+            Format::R16G16B16 => (gl::RGB, gl::UNSIGNED_SHORT, image.pixels.as_slice()),
+            Format::R16G16B16A16 => (gl::RGBA, gl::UNSIGNED_SHORT, image.pixels.as_slice()),
+            Format::R32G32B32FLOAT => (gl::RGB, gl::FLOAT, image.pixels.as_slice()),
+            Format::R32G32B32A32FLOAT => (gl::RGBA, gl::FLOAT, image.pixels.as_slice()),
+        };
 
-    pub fn extract_textures() {}
+        let mut tex = Texture::new();
+        if let Err(e) = tex.load_pixels(image.width, image.height, format, data_type, pixels) {
+            println!("couldn't load texture {}: {e}", texture.index());
+            return None;
+        }
+
+        tex.set_sampling(&Self::extract_sampling(&texture.sampler()));
+
+        textures.push(tex.clone());
+        texture_ids.push(texture.index());
+        Some(tex)
+    }
+
+    /// gray(channels = 1) or gray+alpha(channels = 2) to rgba, size is the bytes per channel
+    fn gray_to_rgba(pixels: &[u8], channels: usize, size: usize) -> Vec<u8> {
+        let opaque = vec![0xff; size];
+        pixels
+            .chunks_exact(channels * size)
+            .flat_map(|pixel| {
+                let (gray, alpha) = pixel.split_at(size);
+                let alpha = if channels == 2 { alpha } else { &opaque[..] };
+                [gray, gray, gray, alpha].concat()
+            })
+            .collect()
+    }
+
+    /// filters left out of the file keep our usual linear filtering
+    fn extract_sampling(sampler: &gltf::texture::Sampler) -> Sampling {
+        use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+        let wrap = |mode| match mode {
+            WrappingMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+            WrappingMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+            WrappingMode::Repeat => gl::REPEAT,
+        };
+
+        let mut sampling = Sampling {
+            wrap_s: wrap(sampler.wrap_s()),
+            wrap_t: wrap(sampler.wrap_t()),
+            ..Sampling::default()
+        };
+
+        if let Some(filter) = sampler.min_filter() {
+            sampling.min_filter = match filter {
+                MinFilter::Nearest => gl::NEAREST,
+                MinFilter::Linear => gl::LINEAR,
+                MinFilter::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+                MinFilter::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+                MinFilter::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+                MinFilter::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
+            };
+        }
+        if let Some(filter) = sampler.mag_filter() {
+            sampling.mag_filter = match filter {
+                MagFilter::Nearest => gl::NEAREST,
+                MagFilter::Linear => gl::LINEAR,
+            };
+        }
+
+        sampling
+    }
+
+    /// we only have two uv sets in a vertex
+    fn uv_set(tex_coord: u32) -> u32 {
+        if tex_coord > 1 {
+            println!("uv set {tex_coord} not supported, using the first one");
+            return 0;
+        }
+        tex_coord
+    }
 
     //_______________________________________________________________________________________________
     // pose loading function along with its helpers
 
+    /// unnamed nodes get named after their index
     fn extract_joint_names(&self, names: &mut Vec<String>) {
         self.document.nodes().for_each(|node| {
-            names.push(node.name().unwrap().to_string());
+            let name = match node.name() {
+                Some(name) => name.to_string(),
+                None => format!("node_{}", node.index()),
+            };
+            names.push(name);
         });
     }
     //_______________________________________________________________________________________________
@@ -237,25 +599,35 @@ impl Gltf {
         });
     }
 
-    fn extract_inverse_bind_mats(&self, inv_poses: &mut Vec<Option<Mat4>>) {
-        inv_poses.resize(self.document.nodes().count(), None);
-
-        // assumes theres only one skin
-        // need to fix this
-        self.document.skins().for_each(|skin| {
+    fn extract_skins(&self, skins: &mut Vec<Skin>) -> Result<(), LoadError> {
+        for skin in self.document.skins() {
             let reader = skin.reader(|buffer| Some(&self.buffers[buffer.index()]));
 
-            let mut inv_bind_mats = Vec::new();
-            if let Some(inverse_bind_mats) = reader.read_inverse_bind_matrices() {
-                inv_bind_mats = inverse_bind_mats.collect();
+            let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+
+            // identity matrices are implied when a skin leaves them out
+            let inverse_bind_mats: Vec<Mat4> = match reader.read_inverse_bind_matrices() {
+                Some(mats) => mats.map(|mat| transpose(&Mat4::from(&mat))).collect(),
+                None => vec![Mat4::IDENTITY; joints.len()],
+            };
+
+            if inverse_bind_mats.len() < joints.len() {
+                return Err(LoadError::Invalid(format!(
+                    "skin {} has {} joints but only {} inverse bind matrices",
+                    skin.index(),
+                    joints.len(),
+                    inverse_bind_mats.len()
+                )));
             }
 
-            for (i, joint) in skin.joints().enumerate() {
-                let inv_mat = &Mat4::from(&inv_bind_mats[i]);
+            skins.push(Skin {
+                name: skin.name().unwrap_or("").to_string(),
+                joints,
+                inverse_bind_mats,
+            });
+        }
 
-                inv_poses[joint.index()] = Some(transpose(inv_mat));
-            }
-        });
+        Ok(())
     }
 
     //_______________________________________________________________________________________________
@@ -265,70 +637,164 @@ impl Gltf {
     // skill issue or not i dont care just please fuckking WORK!
     // it finally works btw :)
 
+    /// key frame times of a channel along with how its sampler interpolates
+    fn read_channel_keys(&self, channel: &gltf::animation::Channel) -> (Vec<f32>, Interpolation) {
+        let mut key_frames_times: Vec<f32> = Vec::new();
+        let reader = channel.reader(|buffer| Some(&self.buffers[buffer.index()]));
+
+        // sparse accessors get filled in by the iterator too
+        if let Some(inputs) = reader.read_inputs() {
+            key_frames_times = inputs.collect();
+        };
+
+        // every channel carries its own interpolation,
+        // even channels animating the same node can differ
+        let interpolation = match channel.sampler().interpolation() {
+            gltf::animation::Interpolation::Step => Interpolation::Constant,
+            gltf::animation::Interpolation::Linear => Interpolation::Linear,
+            gltf::animation::Interpolation::CubicSpline => Interpolation::Cubic,
+        };
+
+        (key_frames_times, interpolation)
+    }
+
     fn extract_animation(
         &self,
         channel: &gltf::animation::Channel,
         track_transform: &mut TransformTrack,
     ) {
-        let mut key_frames_times: Vec<f32> = Vec::new();
+        let (key_frames_times, interpolation) = self.read_channel_keys(channel);
         let reader = channel.reader(|buffer| Some(&self.buffers[buffer.index()]));
 
-        if let Some(inputs) = reader.read_inputs() {
-            match inputs {
-                gltf::accessor::Iter::Standard(times) => {
-                    key_frames_times = times.collect();
-                }
-                gltf::accessor::Iter::Sparse(_) => {
-                    println!("sparce key frames not supported");
-                }
-            }
-        };
-
         if let Some(outputs) = reader.read_outputs() {
             match outputs {
                 gltf::animation::util::ReadOutputs::Translations(translations) => {
-                    track_transform
-                        .position
-                        .frames
-                        .resize(translations.len(), VectorFrame::new());
-                    translations.enumerate().for_each(|(i, translation)| {
-                        track_transform.position.frames[i].m_value = translation;
-                        track_transform.position.frames[i].time = key_frames_times[i];
-                    });
+                    let track = &mut track_transform.position;
+                    track.interpolation = interpolation;
+                    Self::fill_frames(
+                        &mut track.frames,
+                        &key_frames_times,
+                        translations,
+                        interpolation,
+                    );
                 }
                 gltf::animation::util::ReadOutputs::Rotations(rotations) => {
-                    let rotations = rotations.into_f32();
-                    track_transform
-                        .rotation
-                        .frames
-                        .resize(rotations.len(), QuaternionFrame::new());
-                    rotations.enumerate().for_each(|(i, rotation)| {
-                        track_transform.rotation.frames[i].m_value = rotation;
-                        track_transform.rotation.frames[i].time = key_frames_times[i];
-                    });
+                    let track = &mut track_transform.rotation;
+                    track.interpolation = interpolation;
+                    Self::fill_frames(
+                        &mut track.frames,
+                        &key_frames_times,
+                        rotations.into_f32(),
+                        interpolation,
+                    );
                 }
                 gltf::animation::util::ReadOutputs::Scales(scalings) => {
-                    track_transform
-                        .scaling
-                        .frames
-                        .resize(scalings.len(), VectorFrame::ONE);
-                    scalings.enumerate().for_each(|(i, scaling)| {
-                        track_transform.scaling.frames[i].m_value = scaling;
-                        track_transform.scaling.frames[i].time = key_frames_times[i];
-                    });
+                    let track = &mut track_transform.scaling;
+                    track.interpolation = interpolation;
+                    Self::fill_frames(
+                        &mut track.frames,
+                        &key_frames_times,
+                        scalings,
+                        interpolation,
+                    );
                 }
 
+                // handled by extract_morph_animation
                 gltf::animation::util::ReadOutputs::MorphTargetWeights(_) => {}
             }
         }
     }
 
+    /// morph weight outputs hold every targets weight for a key one after the other,
+    /// cubic keys store all in tangents, then all values, then all out tangents
+    fn extract_morph_animation(&self, channel: &gltf::animation::Channel, track: &mut MorphTrack) {
+        let (key_frames_times, interpolation) = self.read_channel_keys(channel);
+        let reader = channel.reader(|buffer| Some(&self.buffers[buffer.index()]));
+
+        let Some(gltf::animation::util::ReadOutputs::MorphTargetWeights(weights)) =
+            reader.read_outputs()
+        else {
+            return;
+        };
+        let weights: Vec<f32> = weights.into_f32().collect();
+
+        let values_per_key = if interpolation == Interpolation::Cubic {
+            3
+        } else {
+            1
+        };
+        if key_frames_times.is_empty() {
+            return;
+        }
+        let target_count = weights.len() / (key_frames_times.len() * values_per_key);
+
+        track.weights.clear();
+        for target in 0..target_count {
+            let values = weights
+                .chunks_exact(target_count)
+                .map(|chunk| [chunk[target]]);
+
+            let mut weight_track = ScalarTrack::new();
+            weight_track.interpolation = interpolation;
+            Self::fill_frames(
+                &mut weight_track.frames,
+                &key_frames_times,
+                values,
+                interpolation,
+            );
+            track.weights.push(weight_track);
+        }
+    }
+
+    /// turn sampler outputs into key frames  
+    /// cubic spline samplers store three values per key: in tangent, value, out tangent
+    /// the tangents are kept as is since Track::sample_cubic scales them by the key spacing
+    fn fill_frames<const N: usize>(
+        frames: &mut Vec<Frame<N>>,
+        times: &[f32],
+        values: impl Iterator<Item = [f32; N]>,
+        interpolation: Interpolation,
+    ) {
+        let values: Vec<[f32; N]> = values.collect();
+        frames.clear();
+
+        // keys without a time(or times without a key) in broken files get dropped
+        if interpolation == Interpolation::Cubic {
+            for (key, time) in values.chunks_exact(3).zip(times) {
+                let mut frame = Frame::new();
+                frame.m_in = key[0];
+                frame.m_value = key[1];
+                frame.m_out = key[2];
+                frame.time = *time;
+                frames.push(frame);
+            }
+        } else {
+            for (value, time) in values.into_iter().zip(times) {
+                let mut frame = Frame::new();
+                frame.m_value = value;
+                frame.time = *time;
+                frames.push(frame);
+            }
+        }
+    }
+
     fn extract_animations(&self, clips: &mut Vec<Clip>) {
         self.document.animations().for_each(|animation| {
             let mut clip = Clip::new();
-            clip.name = animation.name().unwrap().to_string();
+            clip.name = match animation.name() {
+                Some(name) => name.to_string(),
+                None => format!("animation_{}", animation.index()),
+            };
 
             animation.channels().for_each(|channel| {
+                if channel.target().property() == gltf::animation::Property::MorphTargetWeights {
+                    let mut track = MorphTrack::new();
+                    track.id = channel.target().node().index() as u32;
+                    self.extract_morph_animation(&channel, &mut track);
+                    clip.morph_tracks.push(track);
+                    return;
+                }
+
                 //check if track exists
 
                 // bool variable to escape the horrors of the borrow checker
@@ -345,24 +811,10 @@ impl Gltf {
 
                 if !exists {
                     //if it doesn't create a new track
-                    let sampler = &channel.sampler();
-
-                    let mut interpolation = Interpolation::Constant;
-                    if sampler.interpolation() == gltf::animation::Interpolation::Linear {
-                        interpolation = Interpolation::Linear;
-                    } else if sampler.interpolation() == gltf::animation::Interpolation::CubicSpline
-                    {
-                        interpolation = Interpolation::Cubic;
-                    }
-
                     let mut new_track: TransformTrack = TransformTrack::new();
 
                     new_track.id = channel.target().node().index() as u32;
 
-                    new_track.position.interpolation = interpolation;
-                    new_track.rotation.interpolation = interpolation;
-                    new_track.scaling.interpolation = interpolation;
-
                     self.extract_animation(&channel, &mut new_track);
                     clip.tracks.push(new_track);
                 }
@@ -0,0 +1,675 @@
+use crate::src::animation::{
+    clip::Clip, curves::Interpolation, frame::Frame, skeleton::Skeleton, track::Track,
+};
+use crate::src::math::{mat4::*, transform::Transform};
+use crate::src::renderer::{
+    material::{AlphaMode, Materail, Pbr},
+    mesh::Mesh,
+    model::Model,
+    texture::Texture,
+    vertex::Vertex,
+};
+use crate::src::scene::file::{number, write_array};
+
+use json::{object, JsonValue};
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+// gltf writer, the other way around from the loader
+// joints become the first nodes(joint i is node i) followed by a node per mesh instance,
+// instances that came from a node hang off that joint so clips moving it still move them
+// everything lands in a single buffer, images included as png
+// phong materials get turned into rough non metallic pbr ones and lose their specular maps,
+// they're double sided since phong surfaces get drawn without face culling
+// textures are read back from the gpu so this needs the gl context the model was loaded with
+
+// accessor component types and buffer view targets from the spec
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// save a model as .gltf(with a .bin next to it) or .glb, picked by the extension
+pub fn export(model: &Model, path: &Path) -> Result<(), String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    let mut writer = Writer::new();
+    writer.write_model(model);
+
+    match extension.as_deref() {
+        Some("gltf") => {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let bin_path = path.with_extension("bin");
+            let root = writer.finish(Some(&format!("{stem}.bin")));
+
+            fs::write(&bin_path, &writer.bin)
+                .map_err(|e| format!("{}: {e}", bin_path.display()))?;
+            fs::write(path, json::stringify_pretty(root, 2))
+                .map_err(|e| format!("{}: {e}", path.display()))
+        }
+        Some("glb") => {
+            let root = writer.finish(None);
+            fs::write(path, glb(&json::stringify(root), &writer.bin))
+                .map_err(|e| format!("{}: {e}", path.display()))
+        }
+        _ => Err(format!(
+            "\"{}\" should end in .gltf or .glb",
+            path.display()
+        )),
+    }
+}
+
+/// binary container, a json chunk followed by the buffer chunk
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin = bin.to_vec();
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let length = 12 + 8 + json.len() + 8 + bin.len();
+    let mut out = Vec::with_capacity(length);
+
+    out.extend_from_slice(b"glTF");
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(length as u32).to_le_bytes());
+
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(&json);
+
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"BIN\0");
+    out.extend_from_slice(&bin);
+
+    out
+}
+
+fn write_transform(transform: &Transform, out: &mut JsonValue) {
+    out["translation"] = write_array(&transform.translation.to_array());
+    out["rotation"] = write_array(&transform.orientation.to_array());
+    out["scale"] = write_array(&transform.scaling.to_array());
+}
+
+/// top level lists of the document, filled as the model gets walked
+struct Writer {
+    bin: Vec<u8>,
+    buffer_views: Vec<JsonValue>,
+    accessors: Vec<JsonValue>,
+    images: Vec<JsonValue>,
+    textures: Vec<JsonValue>,
+    materials: Vec<JsonValue>,
+    meshes: Vec<JsonValue>,
+    nodes: Vec<JsonValue>,
+    skins: Vec<JsonValue>,
+    animations: Vec<JsonValue>,
+    /// scene root nodes
+    roots: Vec<usize>,
+    /// nodes written for the skeleton, they come first
+    joint_count: usize,
+    /// gl texture id to gltf texture index, None if it couldn't be read back
+    texture_lookup: HashMap<u32, Option<usize>>,
+    /// morph node of the loader to the node written for the mesh
+    morph_nodes: HashMap<usize, usize>,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self {
+            bin: Vec::new(),
+            buffer_views: Vec::new(),
+            accessors: Vec::new(),
+            images: Vec::new(),
+            textures: Vec::new(),
+            materials: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            skins: Vec::new(),
+            animations: Vec::new(),
+            roots: Vec::new(),
+            joint_count: 0,
+            texture_lookup: HashMap::new(),
+            morph_nodes: HashMap::new(),
+        }
+    }
+
+    fn write_model(&mut self, model: &Model) {
+        self.write_skeleton(&model.skeleton);
+        for mesh in model.meshes.iter() {
+            self.write_mesh(mesh);
+        }
+        for clip in model.animations.iter() {
+            self.write_clip(clip);
+        }
+    }
+
+    /// the document, bin uri is None for glb files
+    fn finish(&mut self, bin_uri: Option<&str>) -> JsonValue {
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+
+        let mut root = object! {
+            "asset": { "version": "2.0", "generator": "doohickey" },
+            "scene": 0,
+            "scenes": [{ "nodes": self.roots.clone() }],
+        };
+
+        if !self.bin.is_empty() {
+            let mut buffer = object! { "byteLength": self.bin.len() };
+            if let Some(uri) = bin_uri {
+                buffer["uri"] = uri.into();
+            }
+            root["buffers"] = JsonValue::from(vec![buffer]);
+        }
+
+        // the spec doesn't allow empty lists
+        let lists = [
+            ("bufferViews", &mut self.buffer_views),
+            ("accessors", &mut self.accessors),
+            ("images", &mut self.images),
+            ("textures", &mut self.textures),
+            ("materials", &mut self.materials),
+            ("meshes", &mut self.meshes),
+            ("nodes", &mut self.nodes),
+            ("skins", &mut self.skins),
+            ("animations", &mut self.animations),
+        ];
+        for (key, list) in lists {
+            if !list.is_empty() {
+                root[key] = JsonValue::from(std::mem::take(list));
+            }
+        }
+
+        root
+    }
+
+    //_______________________________________________________________________________________________
+    // buffer data
+
+    fn view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        // accessors need their data aligned to the component size
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+
+        let mut view = object! {
+            "buffer": 0,
+            "byteOffset": self.bin.len(),
+            "byteLength": bytes.len(),
+        };
+        if let Some(target) = target {
+            view["target"] = target.into();
+        }
+
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    /// bounds are required for positions and animation times
+    fn floats<const N: usize>(
+        &mut self,
+        values: &[[f32; N]],
+        target: Option<u32>,
+        bounds: bool,
+    ) -> usize {
+        let kind = match N {
+            1 => "SCALAR",
+            2 => "VEC2",
+            3 => "VEC3",
+            4 => "VEC4",
+            _ => "MAT4",
+        };
+
+        let bytes: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let view = self.view(&bytes, target);
+
+        let mut accessor = object! {
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": kind,
+        };
+        if bounds && !values.is_empty() {
+            let mut min = [f32::MAX; N];
+            let mut max = [f32::MIN; N];
+            for value in values {
+                for i in 0..N {
+                    min[i] = min[i].min(value[i]);
+                    max[i] = max[i].max(value[i]);
+                }
+            }
+            accessor["min"] = write_array(&min);
+            accessor["max"] = write_array(&max);
+        }
+
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn indices(&mut self, values: &[u32]) -> usize {
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+
+        self.accessors.push(object! {
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": values.len(),
+            "type": "SCALAR",
+        });
+        self.accessors.len() - 1
+    }
+
+    /// joints index into the meshes skin, unused slots(-1) point at the first joint with no weight
+    fn joints(&mut self, vertices: &[Vertex]) -> usize {
+        let bytes: Vec<u8> = vertices
+            .iter()
+            .flat_map(|v| v.bone_ids)
+            .flat_map(|id| (id.max(0) as u16).to_le_bytes())
+            .collect();
+        let view = self.view(&bytes, Some(ARRAY_BUFFER));
+
+        self.accessors.push(object! {
+            "bufferView": view,
+            "componentType": UNSIGNED_SHORT,
+            "count": vertices.len(),
+            "type": "VEC4",
+        });
+        self.accessors.len() - 1
+    }
+
+    //_______________________________________________________________________________________________
+    // skeleton
+
+    fn write_skeleton(&mut self, skeleton: &Skeleton) {
+        let pose = &skeleton.rest_pose;
+
+        for (i, joint) in pose.joints.iter().enumerate() {
+            let mut node = object! {};
+            if let Some(name) = skeleton.joint_names.get(i) {
+                node["name"] = name.as_str().into();
+            }
+            write_transform(joint, &mut node);
+
+            let children: Vec<usize> = (0..pose.parents.len())
+                .filter(|j| pose.parents[*j] == i as i32)
+                .collect();
+            if !children.is_empty() {
+                node["children"] = children.into();
+            }
+
+            if pose.parents[i] < 0 {
+                self.roots.push(i);
+            }
+            self.nodes.push(node);
+        }
+        self.joint_count = pose.joints.len();
+
+        for skin in skeleton.skins.iter() {
+            // gltf matrices are column major
+            let mats: Vec<[f32; 16]> = skin
+                .inverse_bind_mats
+                .iter()
+                .map(|mat| {
+                    let columns = transpose(mat).data;
+                    let mut out = [0.0; 16];
+                    for (i, value) in columns.iter().flatten().enumerate() {
+                        out[i] = *value;
+                    }
+                    out
+                })
+                .collect();
+            let inverse_binds = self.floats(&mats, None, false);
+
+            self.skins.push(object! {
+                "name": skin.name.as_str(),
+                "joints": skin.joints.clone(),
+                "inverseBindMatrices": inverse_binds,
+            });
+        }
+    }
+
+    //_______________________________________________________________________________________________
+    // meshes
+
+    fn write_mesh(&mut self, mesh: &Mesh) {
+        // morphed meshes hold blended vertices, the targets go on top of the base ones
+        let vertices = match &mesh.morph {
+            Some(morph) => morph.base(),
+            None => &mesh.vbo.data[..],
+        };
+        if vertices.is_empty() {
+            return;
+        }
+
+        let positions: Vec<[f32; 3]> = vertices.iter().map(|v| v.pos).collect();
+        let normals: Vec<[f32; 3]> = vertices.iter().map(|v| v.norm).collect();
+        let uvs: Vec<[f32; 2]> = vertices.iter().map(|v| v.tex).collect();
+
+        let mut attributes = object! {
+            "POSITION": self.floats(&positions, Some(ARRAY_BUFFER), true),
+            "NORMAL": self.floats(&normals, Some(ARRAY_BUFFER), false),
+            "TEXCOORD_0": self.floats(&uvs, Some(ARRAY_BUFFER), false),
+        };
+
+        // optional attributes only when they carry something
+        if vertices.iter().any(|v| v.tex1 != [0.0; 2]) {
+            let uvs: Vec<[f32; 2]> = vertices.iter().map(|v| v.tex1).collect();
+            attributes["TEXCOORD_1"] = self.floats(&uvs, Some(ARRAY_BUFFER), false).into();
+        }
+        let colored = vertices.iter().any(|v| v.col != [1.0; 3]);
+        if colored {
+            let colors: Vec<[f32; 3]> = vertices.iter().map(|v| v.col).collect();
+            attributes["COLOR_0"] = self.floats(&colors, Some(ARRAY_BUFFER), false).into();
+        }
+        if mesh.skin.is_some() {
+            let weights: Vec<[f32; 4]> = vertices.iter().map(|v| v.weights).collect();
+            attributes["JOINTS_0"] = self.joints(vertices).into();
+            attributes["WEIGHTS_0"] = self.floats(&weights, Some(ARRAY_BUFFER), false).into();
+        }
+
+        let material = self.write_material(mesh, colored);
+        let mut primitive = object! {
+            "attributes": attributes,
+            "material": material,
+        };
+        if let Some(ebo) = &mesh.ebo {
+            primitive["indices"] = self.indices(&ebo.data).into();
+        }
+
+        let mut gltf_mesh = object! {};
+        if let Some(morph) = &mesh.morph {
+            let mut targets = Vec::new();
+            for target in morph.targets.iter() {
+                let mut out = object! {};
+                if !target.positions.is_empty() {
+                    out["POSITION"] = self.floats(&target.positions, None, true).into();
+                }
+                if !target.normals.is_empty() {
+                    out["NORMAL"] = self.floats(&target.normals, None, false).into();
+                }
+                targets.push(out);
+            }
+            primitive["targets"] = targets.into();
+            gltf_mesh["weights"] = write_array(&morph.default_weights);
+        }
+        gltf_mesh["primitives"] = JsonValue::from(vec![primitive]);
+
+        self.meshes.push(gltf_mesh);
+        let index = self.meshes.len() - 1;
+
+        // skinned meshes follow their joints, a single node does
+        let placements = match mesh.skin {
+            Some(_) => vec![Transform::DEFAULT],
+            None => mesh.instances.clone(),
+        };
+        for (i, place) in placements.iter().enumerate() {
+            let joint = match mesh.skin {
+                None => mesh.nodes.get(i).copied().filter(|j| *j < self.joint_count),
+                Some(_) => None,
+            };
+
+            let mut node = object! { "mesh": index };
+            if let Some(skin) = mesh.skin {
+                node["skin"] = skin.into();
+            } else if joint.is_none() {
+                write_transform(place, &mut node);
+            }
+            self.nodes.push(node);
+            let written = self.nodes.len() - 1;
+
+            // instances from a node sit on it untransformed, the rest get placed at the root
+            match joint {
+                Some(joint) => {
+                    let parent = &mut self.nodes[joint];
+                    if !parent["children"].is_array() {
+                        parent["children"] = JsonValue::new_array();
+                    }
+                    let _ = parent["children"].push(written);
+                }
+                None => self.roots.push(written),
+            }
+
+            if let (0, Some(morph)) = (i, &mesh.morph) {
+                self.morph_nodes.insert(morph.node, written);
+            }
+        }
+    }
+
+    //_______________________________________________________________________________________________
+    // materials
+
+    /// colored is whether the mesh got a COLOR_0, gltf multiplies it with the base color
+    fn write_material(&mut self, mesh: &Mesh, colored: bool) -> usize {
+        let material = match &mesh.material {
+            Materail::Pbr(pbr) => self.write_pbr(pbr),
+            Materail::Phong(phong) => {
+                // usual shininess to roughness approximation
+                let roughness = (2.0 / (phong.specular_factor + 2.0)).sqrt();
+                // phong draws the vertex colors and leaves base_color alone,
+                // the loaders put the diffuse color in both so it'd otherwise get applied twice
+                let base = if colored { [1.0; 3] } else { phong.base_color };
+                let mut pbr = object! {
+                    "baseColorFactor": write_array(&[base[0], base[1], base[2], 1.0]),
+                    "metallicFactor": 0.0,
+                    "roughnessFactor": number(roughness),
+                };
+                let diffuse = phong.diffuse_texture.as_ref().or(mesh.texture.as_ref());
+                if let Some(texture) = self.texture_ref(diffuse, 0) {
+                    pbr["baseColorTexture"] = texture;
+                }
+                object! { "pbrMetallicRoughness": pbr, "doubleSided": true }
+            }
+        };
+
+        self.materials.push(material);
+        self.materials.len() - 1
+    }
+
+    fn write_pbr(&mut self, pbr: &Pbr) -> JsonValue {
+        let mut metallic_roughness = object! {
+            "baseColorFactor": write_array(&[
+                pbr.base_color[0],
+                pbr.base_color[1],
+                pbr.base_color[2],
+                pbr.alpha,
+            ]),
+            "metallicFactor": number(pbr.metallic_factor),
+            "roughnessFactor": number(pbr.roughness),
+        };
+        let mut out = object! {
+            "emissiveFactor": write_array(&pbr.emissive),
+            "doubleSided": pbr.double_sided,
+        };
+
+        if let Some(texture) = self.texture_ref(pbr.base_texture.as_ref(), pbr.uv_sets[0]) {
+            metallic_roughness["baseColorTexture"] = texture;
+        }
+        if let Some(texture) = self.texture_ref(pbr.metallic_texture.as_ref(), pbr.uv_sets[1]) {
+            metallic_roughness["metallicRoughnessTexture"] = texture;
+        }
+        if let Some(mut texture) = self.texture_ref(pbr.normal_texture.as_ref(), pbr.uv_sets[2]) {
+            texture["scale"] = number(pbr.normal_scale);
+            out["normalTexture"] = texture;
+        }
+        if let Some(mut texture) = self.texture_ref(pbr.occlusion_texture.as_ref(), pbr.uv_sets[3])
+        {
+            texture["strength"] = number(pbr.occlusion_strength);
+            out["occlusionTexture"] = texture;
+        }
+        if let Some(texture) = self.texture_ref(pbr.emissive_texture.as_ref(), pbr.uv_sets[4]) {
+            out["emissiveTexture"] = texture;
+        }
+        out["pbrMetallicRoughness"] = metallic_roughness;
+
+        match pbr.alpha_mode {
+            AlphaMode::Opaque => {}
+            AlphaMode::Mask => {
+                out["alphaMode"] = "MASK".into();
+                out["alphaCutoff"] = number(pbr.alpha_cutoff);
+            }
+            AlphaMode::Blend => out["alphaMode"] = "BLEND".into(),
+        }
+
+        out
+    }
+
+    /// texture info pointing at the texture, written the first time its used
+    fn texture_ref(&mut self, texture: Option<&Texture>, uv_set: u32) -> Option<JsonValue> {
+        let texture = texture?;
+
+        let index = match self.texture_lookup.get(&texture.id) {
+            Some(index) => *index,
+            None => {
+                let index = self.write_texture(texture);
+                self.texture_lookup.insert(texture.id, index);
+                index
+            }
+        }?;
+
+        Some(object! { "index": index, "texCoord": uv_set })
+    }
+
+    fn write_texture(&mut self, texture: &Texture) -> Option<usize> {
+        let (width, height, pixels) = texture.read_pixels();
+        let image = image::RgbaImage::from_raw(width, height, pixels)?;
+
+        let mut png = Cursor::new(Vec::new());
+        if let Err(e) = image.write_to(&mut png, image::ImageFormat::Png) {
+            println!("couldn't encode texture {}: {e}", texture.id);
+            return None;
+        }
+
+        let view = self.view(png.get_ref(), None);
+        self.images
+            .push(object! { "bufferView": view, "mimeType": "image/png" });
+        self.textures
+            .push(object! { "source": self.images.len() - 1 });
+
+        Some(self.textures.len() - 1)
+    }
+
+    //_______________________________________________________________________________________________
+    // animations
+
+    fn write_clip(&mut self, clip: &Clip) {
+        let mut samplers = Vec::new();
+        let mut channels = Vec::new();
+
+        for track in clip.tracks.iter() {
+            let paths = [
+                ("translation", self.sampler(&track.position)),
+                ("rotation", self.sampler(&track.rotation)),
+                ("scale", self.sampler(&track.scaling)),
+            ];
+            for (path, sampler) in paths {
+                let Some(sampler) = sampler else {
+                    continue;
+                };
+                samplers.push(sampler);
+                channels.push(object! {
+                    "sampler": samplers.len() - 1,
+                    "target": { "node": track.id, "path": path },
+                });
+            }
+        }
+
+        for track in clip.morph_tracks.iter() {
+            let Some(node) = self.morph_nodes.get(&(track.id as usize)).copied() else {
+                continue;
+            };
+            let Some(sampler) = self.weights_sampler(&track.weights) else {
+                continue;
+            };
+            samplers.push(sampler);
+            channels.push(object! {
+                "sampler": samplers.len() - 1,
+                "target": { "node": node, "path": "weights" },
+            });
+        }
+
+        if channels.is_empty() {
+            return;
+        }
+
+        self.animations.push(object! {
+            "name": clip.name.as_str(),
+            "samplers": samplers,
+            "channels": channels,
+        });
+    }
+
+    fn interpolation(interpolation: Interpolation) -> &'static str {
+        match interpolation {
+            Interpolation::Constant => "STEP",
+            Interpolation::Linear => "LINEAR",
+            Interpolation::Cubic => "CUBICSPLINE",
+        }
+    }
+
+    /// cubic keys are written as in tangent, value, out tangent
+    fn keys<const N: usize>(frames: &[Frame<N>], interpolation: Interpolation) -> Vec<[f32; N]> {
+        if interpolation == Interpolation::Cubic {
+            frames
+                .iter()
+                .flat_map(|f| [f.m_in, f.m_value, f.m_out])
+                .collect()
+        } else {
+            frames.iter().map(|f| f.m_value).collect()
+        }
+    }
+
+    fn sampler<const N: usize>(&mut self, track: &Track<N>) -> Option<JsonValue> {
+        if track.frames.is_empty() {
+            return None;
+        }
+
+        let times: Vec<[f32; 1]> = track.frames.iter().map(|f| [f.time]).collect();
+        let input = self.floats(&times, None, true);
+        let output = self.floats(&Self::keys(&track.frames, track.interpolation), None, false);
+
+        Some(object! {
+            "input": input,
+            "output": output,
+            "interpolation": Self::interpolation(track.interpolation),
+        })
+    }
+
+    /// gltf keys every weight at once, so the tracks have to share their times
+    fn weights_sampler(&mut self, tracks: &[Track<1>]) -> Option<JsonValue> {
+        let first = tracks.first()?;
+        if first.frames.is_empty() || tracks.iter().any(|t| t.frames.len() != first.frames.len()) {
+            return None;
+        }
+
+        let times: Vec<[f32; 1]> = first.frames.iter().map(|f| [f.time]).collect();
+        let input = self.floats(&times, None, true);
+
+        // key by key, every target within a key
+        let per_track: Vec<Vec<[f32; 1]>> = tracks
+            .iter()
+            .map(|t| Self::keys(&t.frames, first.interpolation))
+            .collect();
+        let mut values = Vec::new();
+        for key in 0..per_track[0].len() {
+            values.extend(per_track.iter().map(|t| t[key]));
+        }
+        let output = self.floats(&values, None, false);
+
+        Some(object! {
+            "input": input,
+            "output": output,
+            "interpolation": Self::interpolation(first.interpolation),
+        })
+    }
+}
@@ -1 +1,29 @@
+pub mod bvh;
+pub mod cache;
+pub mod collada;
+pub mod error;
 pub mod gltf;
+pub mod gltf_export;
+pub mod obj;
+
+use crate::src::renderer::model::Model;
+
+use error::LoadError;
+use std::path::Path;
+
+/// fills the model from whatever format the files extension says it is
+pub fn load_model(path: &Path, model: &mut Model) -> Result<(), LoadError> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    match extension.as_deref() {
+        Some("gltf") | Some("glb") => gltf::Gltf::new(path)?.populate_model(model),
+        Some("dae") => collada::Collada::new(path)?.populate_model(model),
+        Some("obj") => obj::Obj::new(path)?.populate_model(model),
+        Some("dhm") => cache::Cache::new(path)?.populate_model(model),
+        Some("bvh") => bvh::Bvh::new(path)?.populate_model(model),
+        _ => Err(LoadError::UnknownFormat(path.to_path_buf())),
+    }
+}
@@ -0,0 +1,386 @@
+use crate::src::math::vec3::*;
+use crate::src::renderer::{
+    buffer::*,
+    material::{Materail, Phong},
+    mesh::*,
+    model::Model,
+    texture::Texture,
+    vertex::Vertex,
+};
+
+use super::error::LoadError;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// wavefront obj loader
+// reads positions, uvs, normals and faces(quads and ngons get fanned into triangles)
+// along with the diffuse color, shininess and diffuse/specular maps of the mtl files it points at
+// faces are grouped into one mesh per material, objects and groups don't split meshes
+
+/// a face corner, indices into the files position, uv and normal lists
+type Corner = (usize, Option<usize>, Option<usize>);
+
+/// what a generated normal gets averaged with
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum NormalSource {
+    /// taken from the file
+    File(usize),
+    /// shared by faces around the position in the same smoothing group
+    Smooth(u32),
+    /// face normal of a face with smoothing off
+    Flat(usize),
+}
+
+struct Face {
+    corners: Vec<Corner>,
+    /// 0 is off
+    smoothing: u32,
+}
+
+/// faces drawn with the same material
+struct Batch {
+    material: Option<String>,
+    faces: Vec<Face>,
+}
+
+#[derive(Clone)]
+struct MtlMaterial {
+    diffuse: [f32; 3],
+    shininess: f32,
+    /// image files relative to the obj
+    diffuse_map: Option<String>,
+    specular_map: Option<String>,
+}
+
+impl MtlMaterial {
+    fn default() -> Self {
+        Self {
+            diffuse: [1.0; 3],
+            shininess: 32.0,
+            diffuse_map: None,
+            specular_map: None,
+        }
+    }
+}
+
+pub struct Obj {
+    /// folder the file sits in, mtl files and textures are looked up relative to it
+    folder: PathBuf,
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    batches: Vec<Batch>,
+    materials: HashMap<String, MtlMaterial>,
+}
+
+impl Obj {
+    pub fn new(path: &Path) -> Result<Obj, LoadError> {
+        if !path.is_file() {
+            return Err(LoadError::NotAFile(path.to_path_buf()));
+        }
+
+        let src = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+        let folder = path.parent().unwrap_or(Path::new(""));
+
+        Self::from_str(&src, folder)
+    }
+
+    /// mtl files and textures referenced by the source get looked up relative to folder
+    pub fn from_str(src: &str, folder: &Path) -> Result<Obj, LoadError> {
+        let mut obj = Obj {
+            folder: folder.to_path_buf(),
+            positions: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            batches: Vec::new(),
+            materials: HashMap::new(),
+        };
+
+        let mut material = None;
+        let mut smoothing = 0;
+
+        for (number, line) in src.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut tokens = line.split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+            let error =
+                |message: &str| LoadError::Invalid(format!("line {}: {message}", number + 1));
+
+            match keyword {
+                "v" => {
+                    let v = Self::floats::<3>(tokens).ok_or_else(|| error("bad position"))?;
+                    obj.positions.push(v);
+                }
+                "vn" => {
+                    let n = Self::floats::<3>(tokens).ok_or_else(|| error("bad normal"))?;
+                    obj.normals.push(n);
+                }
+                "vt" => {
+                    let t =
+                        Self::floats::<2>(tokens).ok_or_else(|| error("bad texture coordinate"))?;
+                    obj.uvs.push(t);
+                }
+                "f" => {
+                    let mut corners = Vec::new();
+                    for token in tokens {
+                        corners.push(obj.corner(token).ok_or_else(|| error("bad face index"))?);
+                    }
+                    if corners.len() < 3 {
+                        return Err(error("face with less than 3 corners"));
+                    }
+
+                    // a new batch whenever the material changes
+                    let same = obj.batches.last().is_some_and(|b| b.material == material);
+                    if !same {
+                        obj.batches.push(Batch {
+                            material: material.clone(),
+                            faces: Vec::new(),
+                        });
+                    }
+                    obj.batches
+                        .last_mut()
+                        .unwrap()
+                        .faces
+                        .push(Face { corners, smoothing });
+                }
+                "s" => {
+                    smoothing = match tokens.next() {
+                        Some("off") | None => 0,
+                        Some(group) => group.parse().map_err(|_| error("bad smoothing group"))?,
+                    };
+                }
+                "usemtl" => material = Some(Self::rest(line, keyword)),
+                "mtllib" => {
+                    let file = Self::rest(line, keyword);
+                    match fs::read_to_string(obj.folder.join(&file)) {
+                        Ok(mtl) => obj.read_mtl(&mtl),
+                        Err(e) => println!("couldn't load material library {file}: {e}"),
+                    }
+                }
+                // objects, groups, lines and points don't matter here
+                _ => {}
+            }
+        }
+
+        Ok(obj)
+    }
+
+    /// text after the keyword, names and files can have spaces in them
+    fn rest(line: &str, keyword: &str) -> String {
+        line[keyword.len()..].trim().to_string()
+    }
+
+    fn floats<'a, const N: usize>(mut tokens: impl Iterator<Item = &'a str>) -> Option<[f32; N]> {
+        let mut out = [0.0; N];
+        for value in out.iter_mut() {
+            *value = tokens.next()?.parse().ok()?;
+        }
+        Some(out)
+    }
+
+    /// indices start at 1, negative ones count back from the last element read
+    fn index(token: &str, len: usize) -> Option<usize> {
+        let i: i64 = token.parse().ok()?;
+        let i = if i < 0 { len as i64 + i } else { i - 1 };
+        (0..len as i64).contains(&i).then_some(i as usize)
+    }
+
+    /// "v", "v/t", "v//n" or "v/t/n"
+    fn corner(&self, token: &str) -> Option<Corner> {
+        let mut parts = token.split('/');
+        let v = Self::index(parts.next()?, self.positions.len())?;
+
+        let t = match parts.next() {
+            Some("") | None => None,
+            Some(t) => Some(Self::index(t, self.uvs.len())?),
+        };
+        let n = match parts.next() {
+            Some("") | None => None,
+            Some(n) => Some(Self::index(n, self.normals.len())?),
+        };
+
+        Some((v, t, n))
+    }
+
+    fn read_mtl(&mut self, src: &str) {
+        let mut current: Option<(String, MtlMaterial)> = None;
+
+        for line in src.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut tokens = line.split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+
+            if keyword == "newmtl" {
+                if let Some((name, material)) = current.take() {
+                    self.materials.insert(name, material);
+                }
+                current = Some((Self::rest(line, keyword), MtlMaterial::default()));
+                continue;
+            }
+            let Some((_, material)) = current.as_mut() else {
+                continue;
+            };
+
+            match keyword {
+                "Kd" => {
+                    if let Some(kd) = Self::floats::<3>(tokens) {
+                        material.diffuse = kd;
+                    }
+                }
+                "Ns" => {
+                    if let Some([ns]) = Self::floats::<1>(tokens) {
+                        material.shininess = ns;
+                    }
+                }
+                // map options(-s, -o, -bm...) come before the file, taking the last token skips them
+                "map_Kd" => material.diffuse_map = tokens.last().map(String::from),
+                "map_Ks" => material.specular_map = tokens.last().map(String::from),
+                _ => {}
+            }
+        }
+
+        if let Some((name, material)) = current {
+            self.materials.insert(name, material);
+        }
+    }
+
+    //_______________________________________________________________________________________________
+    pub fn populate_model(&self, model: &mut Model) -> Result<(), LoadError> {
+        self.extract_meshes_and_textures(&mut model.meshes, &mut model.textures);
+        Ok(())
+    }
+
+    pub fn extract_meshes_and_textures(&self, meshes: &mut Vec<Mesh>, textures: &mut Vec<Texture>) {
+        // textures get shared between meshes, keyed by file
+        let mut loaded: HashMap<String, Option<Texture>> = HashMap::new();
+        let mut load = |file: &Option<String>| -> Option<Texture> {
+            let file = file.as_ref()?;
+            loaded
+                .entry(file.clone())
+                .or_insert_with(|| {
+                    let mut tex = Texture::new();
+                    match tex.from(&self.folder.join(file)) {
+                        Ok(()) => {
+                            textures.push(tex.clone());
+                            Some(tex)
+                        }
+                        Err(e) => {
+                            println!("couldn't load texture {e}");
+                            None
+                        }
+                    }
+                })
+                .clone()
+        };
+
+        for batch in self.batches.iter() {
+            let material = batch
+                .material
+                .as_ref()
+                .and_then(|name| self.materials.get(name))
+                .cloned()
+                .unwrap_or(MtlMaterial::default());
+
+            let mut mesh = self.build_mesh(batch, material.diffuse);
+
+            let diffuse_texture = load(&material.diffuse_map);
+            mesh.texture = diffuse_texture.clone();
+            mesh.material = Materail::Phong(Phong {
+                base_color: material.diffuse,
+                specular_factor: material.shininess.max(1.0),
+                diffuse_texture,
+                specular_texture: load(&material.specular_map),
+                ..Phong::default()
+            });
+
+            mesh.create();
+            meshes.push(mesh);
+        }
+    }
+
+    fn build_mesh(&self, batch: &Batch, color: [f32; 3]) -> Mesh {
+        // normals of corners without one, summed up over the faces sharing them
+        let mut generated: HashMap<(usize, NormalSource), Vec3> = HashMap::new();
+        let mut sources = Vec::new();
+
+        for (f, face) in batch.faces.iter().enumerate() {
+            let normal = self.face_normal(face);
+
+            for &(v, _, n) in face.corners.iter() {
+                let source = match n {
+                    Some(n) => NormalSource::File(n),
+                    None if face.smoothing == 0 => NormalSource::Flat(f),
+                    None => NormalSource::Smooth(face.smoothing),
+                };
+                if n.is_none() {
+                    let sum = generated.entry((v, source)).or_insert(Vec3::ZERO);
+                    *sum = *sum + normal;
+                }
+                sources.push(source);
+            }
+        }
+
+        let mut mesh = Mesh::default();
+        let mut ebo = EBO::default();
+        let mut lookup: HashMap<(usize, Option<usize>, NormalSource), u32> = HashMap::new();
+        let mut sources = sources.into_iter();
+
+        for face in batch.faces.iter() {
+            let mut indices = Vec::new();
+
+            for &(v, t, n) in face.corners.iter() {
+                let source = sources.next().unwrap();
+                let index = *lookup.entry((v, t, source)).or_insert_with(|| {
+                    let norm = match n {
+                        Some(n) => self.normals[n],
+                        None => {
+                            let sum = generated[&(v, source)];
+                            if sum.len() > 0.0 {
+                                sum.unit().to_array()
+                            } else {
+                                [0.0, 1.0, 0.0]
+                            }
+                        }
+                    };
+
+                    mesh.vbo.data.push(Vertex {
+                        pos: self.positions[v],
+                        norm,
+                        tex: t.map(|t| self.uvs[t]).unwrap_or([0.0; 2]),
+                        col: color,
+                        ..Vertex::DEFAULT
+                    });
+                    mesh.vbo.data.len() as u32 - 1
+                });
+                indices.push(index);
+            }
+
+            // fan out from the first corner
+            for i in 1..indices.len() - 1 {
+                ebo.data
+                    .extend_from_slice(&[indices[0], indices[i], indices[i + 1]]);
+            }
+        }
+
+        mesh.ebo = Some(ebo);
+        mesh
+    }
+
+    /// area weighted so big faces count for more around a shared corner
+    fn face_normal(&self, face: &Face) -> Vec3 {
+        // newell's method, works for ngons that aren't perfectly flat too
+        let mut normal = Vec3::ZERO;
+        for (i, corner) in face.corners.iter().enumerate() {
+            let a = Vec3::from(&self.positions[corner.0]);
+            let b = Vec3::from(&self.positions[face.corners[(i + 1) % face.corners.len()].0]);
+            normal = normal + cross(&a, &b);
+        }
+        normal * 0.5
+    }
+}
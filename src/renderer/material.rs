@@ -3,22 +3,114 @@ use super::texture::Texture;
 
 use crate::src::math::vec3::Vec3;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PatternKind {
+    None,
+    Checkered,
+    Striped,
+}
+
+/// what the pattern is laid out over
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PatternSpace {
+    /// model space position, pattern sticks to the object no matter its uvs
+    Object,
+    /// texture coordinates
+    Uv,
+}
+
+/// procedural two color surface pattern  
+/// the first color is whatever the surface already has(vertex color, texture or base color)
+/// and the pattern mixes the second one in on top
+#[derive(Clone, Copy, Debug)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub space: PatternSpace,
+    pub color: [f32; 3],
+    /// cells(or stripes) per unit
+    pub scale: f32,
+    /// fraction of each stripe period covered by the second color
+    pub width: f32,
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Self {
+            kind: PatternKind::None,
+            space: PatternSpace::Object,
+            color: [0.0; 3],
+            scale: 1.0,
+            width: 0.5,
+        }
+    }
+}
+
+impl Pattern {
+    fn configure_shader(&self, program: &Program) {
+        let kind = match self.kind {
+            PatternKind::None => 0,
+            PatternKind::Checkered => 1,
+            PatternKind::Striped => 2,
+        };
+        program.update_int("patternType", kind);
+        program.update_int(
+            "patternObjectSpace",
+            (self.space == PatternSpace::Object).into(),
+        );
+        program.update_vec3("patternColor", Vec3::from(&self.color));
+        program.update_float("patternScale", self.scale);
+        program.update_float("patternWidth", self.width);
+    }
+}
+
 #[derive(Clone)]
 pub struct Phong {
     pub base_color: [f32; 3],
     pub specular_factor: f32,
     pub diffuse_texture: Option<Texture>,
     pub specular_texture: Option<Texture>,
+    pub pattern: Pattern,
+}
+
+/// how the alpha of the base color is treated, same meaning as in glTF
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+    /// alpha ignored
+    Opaque,
+    /// fully transparent below the cutoff, opaque above it
+    Mask,
+    /// blended with whatever is behind
+    Blend,
 }
 
+/// metallic-roughness material
+/// texture slots get multiplied with their factors like glTF does
 #[derive(Clone)]
 pub struct Pbr {
+    /// ambient light strength
     pub ao: f32,
     pub base_color: [f32; 3],
+    pub alpha: f32,
     pub roughness: f32,
     pub metallic_factor: f32,
+    pub emissive: [f32; 3],
     pub base_texture: Option<Texture>,
+    /// roughness in the green channel, metalness in the blue one
     pub metallic_texture: Option<Texture>,
+    /// tangent space normal map
+    pub normal_texture: Option<Texture>,
+    pub normal_scale: f32,
+    /// occlusion in the red channel
+    pub occlusion_texture: Option<Texture>,
+    pub occlusion_strength: f32,
+    pub emissive_texture: Option<Texture>,
+    /// uv set each texture reads(0 or 1) in the order base, metallic, normal, occlusion, emissive
+    pub uv_sets: [u32; 5],
+    pub alpha_mode: AlphaMode,
+    pub alpha_cutoff: f32,
+    /// back faces get culled unless set
+    pub double_sided: bool,
+    pub pattern: Pattern,
 }
 
 impl Default for Phong {
@@ -28,16 +120,33 @@ impl Default for Phong {
             base_color: [1.0; 3],
             diffuse_texture: None,
             specular_texture: None,
+            pattern: Pattern::default(),
         }
     }
 }
 
 impl Phong {
+    /// texture unit the specular map is bound to, the diffuse one goes with the mesh texture on 1
+    pub const SPECULAR_UNIT: u32 = 2;
+
     fn configure_shader(&self, program: &Program) {
         program.update_vec3("baseColor", Vec3::from(&self.base_color));
         program.update_float("specular_strength", self.specular_factor);
         program.update_int("hasDiffuseTex", self.diffuse_texture.is_some().into());
         program.update_int("hasSpecularTex", self.specular_texture.is_some().into());
+        if let Some(texture) = &self.specular_texture {
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0 + Self::SPECULAR_UNIT);
+            }
+            texture.bind();
+        }
+        self.pattern.configure_shader(program);
+
+        // phong surfaces have always been drawn from both sides without blending
+        unsafe {
+            gl::Disable(gl::CULL_FACE);
+            gl::Disable(gl::BLEND);
+        }
     }
 }
 
@@ -47,20 +156,110 @@ impl Default for Pbr {
             ao: 0.1,
             roughness: 0.5,
             base_color: [1.0; 3],
+            alpha: 1.0,
             metallic_factor: 0.5,
+            emissive: [0.0; 3],
             base_texture: None,
             metallic_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            occlusion_texture: None,
+            occlusion_strength: 1.0,
+            emissive_texture: None,
+            uv_sets: [0; 5],
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+            pattern: Pattern::default(),
         }
     }
 }
 impl Pbr {
+    // texture units the samplers are set to, 0 is the shadow map
+    pub const BASE_UNIT: u32 = 1;
+    pub const METALLIC_UNIT: u32 = 2;
+    pub const NORMAL_UNIT: u32 = 3;
+    pub const OCCLUSION_UNIT: u32 = 4;
+    pub const EMISSIVE_UNIT: u32 = 5;
+
     fn configure_shader(&self, program: &Program) {
         program.update_vec3("baseColor", Vec3::from(&self.base_color));
+        program.update_float("alpha", self.alpha);
         program.update_float("metallicFactor", self.metallic_factor);
         program.update_float("roughness", self.roughness);
         program.update_float("ao", self.ao);
-        program.update_int("hasBaseTexture", self.metallic_texture.is_some().into());
-        program.update_int("hasBaseTexture", self.metallic_texture.is_some().into());
+        program.update_vec3("emissive", Vec3::from(&self.emissive));
+        program.update_float("normalScale", self.normal_scale);
+        program.update_float("occlusionStrength", self.occlusion_strength);
+
+        let slots = [
+            (
+                "hasBaseTexture",
+                "baseUv",
+                &self.base_texture,
+                Self::BASE_UNIT,
+            ),
+            (
+                "hasMetallicTexture",
+                "metallicUv",
+                &self.metallic_texture,
+                Self::METALLIC_UNIT,
+            ),
+            (
+                "hasNormalTexture",
+                "normalUv",
+                &self.normal_texture,
+                Self::NORMAL_UNIT,
+            ),
+            (
+                "hasOcclusionTexture",
+                "occlusionUv",
+                &self.occlusion_texture,
+                Self::OCCLUSION_UNIT,
+            ),
+            (
+                "hasEmissiveTexture",
+                "emissiveUv",
+                &self.emissive_texture,
+                Self::EMISSIVE_UNIT,
+            ),
+        ];
+        for (i, (flag, uv, texture, unit)) in slots.into_iter().enumerate() {
+            program.update_int(flag, texture.is_some().into());
+            program.update_int(uv, self.uv_sets[i] as i32);
+            if let Some(texture) = texture {
+                unsafe {
+                    gl::ActiveTexture(gl::TEXTURE0 + unit);
+                }
+                texture.bind();
+            }
+        }
+
+        let alpha_mode = match self.alpha_mode {
+            AlphaMode::Opaque => 0,
+            AlphaMode::Mask => 1,
+            AlphaMode::Blend => 2,
+        };
+        program.update_int("alphaMode", alpha_mode);
+        program.update_float("alphaCutoff", self.alpha_cutoff);
+        program.update_int("doubleSided", self.double_sided.into());
+        self.pattern.configure_shader(program);
+
+        unsafe {
+            if self.double_sided {
+                gl::Disable(gl::CULL_FACE);
+            } else {
+                gl::Enable(gl::CULL_FACE);
+                gl::CullFace(gl::BACK);
+            }
+
+            if self.alpha_mode == AlphaMode::Blend {
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            } else {
+                gl::Disable(gl::BLEND);
+            }
+        }
     }
 }
 
@@ -75,6 +274,29 @@ impl Materail {
         Self::Phong(Phong::default())
     }
 
+    pub fn is_pbr(&self) -> bool {
+        matches!(self, Self::Pbr(_))
+    }
+
+    /// whether the surface is see through and has to be drawn after the opaque ones
+    pub fn blended(&self) -> bool {
+        matches!(self, Self::Pbr(pbr) if pbr.alpha_mode == AlphaMode::Blend)
+    }
+
+    pub fn pattern(&self) -> &Pattern {
+        match self {
+            Self::Phong(phong) => &phong.pattern,
+            Self::Pbr(pbr) => &pbr.pattern,
+        }
+    }
+
+    pub fn pattern_mut(&mut self) -> &mut Pattern {
+        match self {
+            Self::Phong(phong) => &mut phong.pattern,
+            Self::Pbr(pbr) => &mut pbr.pattern,
+        }
+    }
+
     pub fn configure_shader(&self, program: &Program) {
         match self {
             Self::Phong(phong) => {
@@ -1,9 +1,12 @@
 use super::buffer::*;
 use super::material::*;
+use super::morph::Morph;
 use super::texture::Texture;
 use super::vao::Vao;
 use super::vertex::Vertex;
 
+use crate::src::math::transform::Transform;
+
 #[derive(Clone)]
 pub struct Mesh {
     pub vao: Vao,
@@ -11,6 +14,15 @@ pub struct Mesh {
     pub ebo: Option<EBO>,
     pub texture: Option<Texture>,
     pub material: Materail,
+    /// placement of each copy of the mesh relative to its model, drawn once per entry
+    pub instances: Vec<Transform>,
+    /// node(joint of the models skeleton) each instance came from, parallel to instances  
+    /// empty for formats without a node hierarchy
+    pub nodes: Vec<usize>,
+    /// index into the models skeleton skins, None for meshes that arent skinned
+    pub skin: Option<usize>,
+    /// blend shapes, None for meshes without any
+    pub morph: Option<Morph>,
 }
 
 impl Mesh {
@@ -21,6 +33,10 @@ impl Mesh {
             ebo: None,
             texture: None,
             material: Materail::default(),
+            instances: vec![Transform::DEFAULT],
+            nodes: Vec::new(),
+            skin: None,
+            morph: None,
         }
     }
 
@@ -42,6 +58,19 @@ impl Mesh {
         Vao::unbind();
     }
 
+    /// re-blend the morph targets, only touches the gpu when the weights actually changed
+    pub fn set_morph_weights(&mut self, weights: &[f32]) {
+        let Some(morph) = &mut self.morph else {
+            return;
+        };
+        if morph.weights == weights {
+            return;
+        }
+
+        morph.apply(weights, &mut self.vbo.data);
+        self.vbo.bind(gl::ARRAY_BUFFER);
+    }
+
     pub fn textured(&self) -> bool {
         self.texture.is_some()
     }
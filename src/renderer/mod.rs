@@ -2,6 +2,8 @@ pub mod buffer;
 pub mod material;
 pub mod mesh;
 pub mod model;
+pub mod morph;
+pub mod primitives;
 pub mod shaders;
 pub mod shadows;
 pub mod texture;
@@ -7,21 +7,31 @@ use crate::src::math::quaternion::Quat;
 use crate::src::math::transform::Transform;
 use crate::src::math::vec3::Vec3;
 
+use crate::src::animation::additive::make_additive_from_first_frame;
 use crate::src::animation::clip::Clip;
+use crate::src::animation::layer::{Layer, LayerMode};
+use crate::src::animation::mask::JointMask;
 use crate::src::animation::pose::Pose;
 use crate::src::animation::skeleton::Skeleton;
+use crate::src::animation::state_machine::{Motion, State, StateMachine};
 
 // i seriously need to refactor this mess
 
 #[derive(Clone)]
 pub struct Model {
+    /// file the model was loaded from, empty for models built in code
+    pub file: String,
     pub meshes: Vec<Mesh>,
     pub transform: Transform,
     pub animations: Vec<Clip>, //optional
     pub skeleton: Skeleton,    //optional
-    pub current_anim: usize,   //refactor
-    pub play_animation: bool,  //refactor
     pub final_pose: Pose,      //refactor
+    /// picks the pose from the clips, None shows the rest pose
+    pub animator: Option<StateMachine>,
+    /// clips applied on top of the state machines pose, in order
+    pub layers: Vec<Layer>,
+    /// time the animation was last updated at, None until the first update
+    last_time: Option<f32>,
 
     pub textures: Vec<Texture>,
 }
@@ -29,14 +39,16 @@ pub struct Model {
 impl Model {
     pub fn default() -> Self {
         Self {
+            file: String::new(),
             transform: Transform::DEFAULT,
             meshes: Vec::new(),
             skeleton: Skeleton::new(),
             textures: Vec::new(),
             animations: Vec::new(),
-            play_animation: false,
-            current_anim: 0,
             final_pose: Pose::new(),
+            animator: None,
+            layers: Vec::new(),
+            last_time: None,
         }
     }
 
@@ -56,67 +68,184 @@ impl Model {
         self.transform.orientation = quat;
     }
 
+    pub fn joint_index(&self, name: &str) -> Option<usize> {
+        self.skeleton.joint_names.iter().position(|n| n == name)
+    }
+
+    /// model space transform of a joint in the current pose
+    pub fn joint_transform(&self, joint: usize) -> Option<Transform> {
+        let pose = if self.animator.is_some() {
+            &self.final_pose
+        } else {
+            &self.skeleton.rest_pose
+        };
+
+        if joint < pose.joints.len() {
+            Some(pose.get_global_tranform(joint))
+        } else {
+            None
+        }
+    }
+
+    /// whether the model has any skins, skinned models need the animation shader
+    pub fn skinned(&self) -> bool {
+        !self.skeleton.skins.is_empty()
+    }
+
     // can only choose one lighting model per object
     pub fn render(&mut self, shader: &shaders::Program) {
-        let mats = &self.get_pose();
-        for i in 0..mats.len() {
-            shader.update_mat4(format!("boneMats[{i}]").as_str(), &mats[i]);
-        }
+        self.render_filtered(shader, |_| true);
+    }
 
-        shader.update_mat4("transform", &self.transform.to_mat());
+    /// only draws the meshes the filter picks, so meshes with different materials
+    /// can go through different programs
+    pub fn render_filtered<F>(&mut self, shader: &shaders::Program, filter: F)
+    where
+        F: Fn(&Mesh) -> bool,
+    {
+        // meshes sharing a skin share a palette, only resend it when the skin changes
+        let mut bound_skin = None;
+
+        for i in 0..self.meshes.len() {
+            if !filter(&self.meshes[i]) {
+                continue;
+            }
 
-        for mesh in self.meshes.iter_mut() {
+            if let Some(skin) = self.meshes[i].skin {
+                if bound_skin != Some(skin) {
+                    for (j, mat) in self.get_pose(skin).iter().enumerate() {
+                        shader.update_mat4(format!("boneMats[{j}]").as_str(), mat);
+                    }
+                    bound_skin = Some(skin);
+                }
+            }
+
+            let mesh = &mut self.meshes[i];
             shader.update_int("textured", mesh.textured() as i32);
-            mesh.render();
+            mesh.material.configure_shader(shader);
+
+            for instance in mesh.instances.iter() {
+                let transform = Transform::combine(&self.transform, instance);
+                shader.update_mat4("transform", &transform.to_mat());
+                mesh.render();
+            }
         }
     }
 
-    pub fn update_animation(&mut self, time: f32) {
-        if self.play_animation {
-            self.final_pose = self.skeleton.rest_pose.clone();
-            // extract animation for each joint(bone)
-            self.animations[self.current_anim].sample(&mut self.final_pose, time);
+    /// loop one of the models clips, replacing whatever state machine was running
+    pub fn play(&mut self, clip: usize) {
+        if let Some(anim) = self.animations.get(clip) {
+            self.animator = Some(StateMachine::single(clip, &anim.name));
         }
     }
 
-    fn get_pose(&mut self) -> Vec<Mat4> {
-        let mut final_mats = Vec::new();
-
-        let len = self.skeleton.rest_pose.joints.len();
-        //black holders for mats which arent being used
-        final_mats.resize(len, Mat4::IDENTITY);
-
-        if self.play_animation {
-            // the match statements are a slight attempt at optimization,
-            // to reduce the amount of matrix multiplication going on ofcourse
-            // only multiply with the joint that are actually sent to the shader
-            // not sure if this is significant though
-            for i in 0..len {
-                if let Some(inverse_pose) = self.skeleton.inverse_bind_pose[i] {
-                    // only get global transforms for joints being used
-                    // to reduce iterations perfomed ofcourse
-                    let world = self.final_pose.get_global_tranform(i);
-
-                    final_mats[i] = world.to_mat() * inverse_pose;
-                } else {
-                    // do nothing if joint contains no inverse bind pose
-                    // final_mats[i] = world.to_mat() ;
-                }
-            }
-        } else {
-            for i in 0..len {
-                // only get global transforms for joints being used
-                if let Some(inverse_pose) = self.skeleton.inverse_bind_pose[i] {
-                    let world = self.skeleton.rest_pose.get_global_tranform(i);
-
-                    final_mats[i] = world.to_mat() * inverse_pose;
-                } else {
-                    // do nothing if joint contains no inverse bind pose
-                    // final_mats[i] = world.to_mat();
-                }
+    /// back to the rest pose
+    pub fn stop(&mut self) {
+        self.animator = None;
+    }
+
+    /// fade from whatever is playing to a clip over duration seconds instead of popping to it  
+    /// the state machine gets a state for the clip if it doesn't have one yet
+    pub fn fade_to(&mut self, clip: usize, duration: f32) {
+        let Some(anim) = self.animations.get(clip) else {
+            return;
+        };
+        let Some(animator) = self.animator.as_mut() else {
+            self.play(clip);
+            return;
+        };
+
+        let state = animator
+            .states
+            .iter()
+            .position(|s| s.motion == Motion::Clip(clip))
+            .unwrap_or_else(|| animator.add_state(State::new(&anim.name, Motion::Clip(clip))));
+        animator.fade_to_state(state, duration);
+    }
+
+    /// layer one of the models clips over the others, made additive against its first frame  
+    /// gives back the layers index so its weight can be changed later
+    pub fn add_layer(
+        &mut self,
+        clip: usize,
+        weight: f32,
+        mask: Option<JointMask>,
+    ) -> Option<usize> {
+        let clip = self.animations.get(clip)?;
+        let additive = make_additive_from_first_frame(clip, &self.skeleton.rest_pose);
+
+        let mut layer = Layer::new(additive, LayerMode::Additive, weight);
+        layer.mask = mask;
+        self.layers.push(layer);
+        Some(self.layers.len() - 1)
+    }
+
+    /// play one of the models clips over the others on the joints the mask picks,
+    /// e.g. waving with the upper body while the legs keep walking
+    pub fn add_override_layer(
+        &mut self,
+        clip: usize,
+        weight: f32,
+        mask: Option<JointMask>,
+    ) -> Option<usize> {
+        let clip = self.animations.get(clip)?.clone();
+
+        let mut layer = Layer::new(clip, LayerMode::Override, weight);
+        layer.mask = mask;
+        self.layers.push(layer);
+        Some(self.layers.len() - 1)
+    }
+
+    pub fn update_animation(&mut self, time: f32) {
+        // models added to a running world start from wherever the clock is
+        let dt = self.last_time.map_or(0.0, |last| (time - last).max(0.0));
+        self.last_time = Some(time);
+
+        let Some(animator) = self.animator.as_mut() else {
+            return;
+        };
+        animator.update(&mut self.animations, &self.skeleton.rest_pose, dt);
+        self.final_pose.clone_from(animator.pose());
+        for layer in self.layers.iter_mut() {
+            layer.apply(&mut self.final_pose, &self.skeleton.rest_pose, dt);
+        }
+
+        // blend shape weights follow the clip with the most weight
+        let Some((clip, time)) = animator.current_clip(&self.animations) else {
+            return;
+        };
+        let Some(clip) = self.animations.get(clip) else {
+            return;
+        };
+        for mesh in self.meshes.iter_mut() {
+            if let Some(morph) = &mesh.morph {
+                let mut weights = morph.default_weights.clone();
+                clip.sample_morph(morph.node as u32, &mut weights, time);
+                mesh.set_morph_weights(&weights);
             }
         }
+    }
 
-        final_mats
+    /// skinning matrices for one skin, parallel to its joints
+    fn get_pose(&self, skin: usize) -> Vec<Mat4> {
+        let Some(skin) = self.skeleton.skins.get(skin) else {
+            return Vec::new();
+        };
+
+        let pose = if self.animator.is_some() {
+            &self.final_pose
+        } else {
+            &self.skeleton.rest_pose
+        };
+
+        // only get global transforms for joints the skin actually uses
+        skin.joints
+            .iter()
+            .zip(skin.inverse_bind_mats.iter())
+            .map(|(joint, inverse_pose)| {
+                let world = pose.get_global_tranform(*joint);
+                world.to_mat() * *inverse_pose
+            })
+            .collect()
     }
 }
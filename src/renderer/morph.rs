@@ -0,0 +1,82 @@
+use super::vertex::Vertex;
+
+// morph targets(blend shapes) blended on the cpu
+// the blended vertices get re-uploaded whenever the weights change
+// plenty fast for a handful of facial shapes
+
+/// per vertex offsets for one target, either list can be empty if the target doesn't move it
+#[derive(Clone, Default)]
+pub struct MorphTarget {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+}
+
+#[derive(Clone)]
+pub struct Morph {
+    pub targets: Vec<MorphTarget>,
+    /// weights used when no animation drives them
+    pub default_weights: Vec<f32>,
+    /// weights the current vertex buffer was blended with
+    pub weights: Vec<f32>,
+    /// node whose weight animation drives this mesh
+    pub node: usize,
+    base: Vec<Vertex>,
+}
+
+impl Morph {
+    /// base is the unmorphed vertex data
+    pub fn new(
+        targets: Vec<MorphTarget>,
+        default_weights: Vec<f32>,
+        node: usize,
+        base: Vec<Vertex>,
+    ) -> Self {
+        Self {
+            weights: vec![0.0; targets.len()],
+            targets,
+            default_weights,
+            node,
+            base,
+        }
+    }
+
+    /// the unmorphed vertices
+    pub fn base(&self) -> &[Vertex] {
+        &self.base
+    }
+
+    /// blend the targets into out(parallel to the base vertices) with the given weights
+    pub fn apply(&mut self, weights: &[f32], out: &mut [Vertex]) {
+        self.weights.clear();
+        self.weights.extend_from_slice(weights);
+        out.copy_from_slice(&self.base);
+
+        for (target, weight) in self.targets.iter().zip(weights.iter()) {
+            if *weight == 0.0 {
+                continue;
+            }
+
+            for (vert, offset) in out.iter_mut().zip(target.positions.iter()) {
+                for (v, o) in vert.pos.iter_mut().zip(offset.iter()) {
+                    *v += o * weight;
+                }
+            }
+            for (vert, offset) in out.iter_mut().zip(target.normals.iter()) {
+                for (v, o) in vert.norm.iter_mut().zip(offset.iter()) {
+                    *v += o * weight;
+                }
+            }
+        }
+
+        // offsets are added before renormalizing, as the glTF spec does it
+        if self.targets.iter().any(|target| !target.normals.is_empty()) {
+            for vert in out.iter_mut() {
+                let [x, y, z] = vert.norm;
+                let len = (x * x + y * y + z * z).sqrt();
+                if len > 0.0 {
+                    vert.norm = [x / len, y / len, z / len];
+                }
+            }
+        }
+    }
+}
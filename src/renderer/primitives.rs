@@ -0,0 +1,330 @@
+use super::buffer::*;
+use super::material::*;
+use super::mesh::Mesh;
+use super::vertex::Vertex;
+
+use crate::src::math::{misc::PIE, vec3::*};
+
+// procedural shapes for debug geometry, blockouts and test scenes
+// shapes are centered on the origin with a radius(or half size) of 1
+// use the model transform to size them
+// triangles wind counter clockwise when looking at the outside of the shape
+
+/// uv sphere, lats goes pole to pole and longs around the equator
+pub fn sphere(lats: u32, longs: u32, color: [f32; 3]) -> Mesh {
+    let lats = lats.max(2);
+    let longs = longs.max(3);
+
+    let mut vertices = Vec::new();
+    for i in 0..=lats {
+        let theta = PIE * i as f32 / lats as f32;
+        for j in 0..=longs {
+            let phi = 2.0 * PIE * j as f32 / longs as f32;
+            let norm = vec3(
+                f32::sin(theta) * f32::cos(phi),
+                f32::cos(theta),
+                f32::sin(theta) * f32::sin(phi),
+            );
+            let tex = [j as f32 / longs as f32, 1.0 - i as f32 / lats as f32];
+
+            vertices.push(vertex(norm, norm, tex, color));
+        }
+    }
+
+    let mut indices = Vec::new();
+    grid_indices(lats, longs, 0, &mut indices);
+
+    into_mesh(vertices, indices, color)
+}
+
+/// every face of a color cube gets its own color, handy for telling orientation apart
+pub fn cube(color: [f32; 3], color_cube: bool) -> Mesh {
+    // (normal, u, v) where u x v = normal so faces come out counter clockwise
+    let faces = [
+        (
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 0.0, -1.0),
+            vec3(0.0, 1.0, 0.0),
+        ),
+        (
+            vec3(-1.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+            vec3(0.0, 1.0, 0.0),
+        ),
+        (
+            vec3(0.0, 1.0, 0.0),
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 0.0, -1.0),
+        ),
+        (
+            vec3(0.0, -1.0, 0.0),
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+        ),
+        (
+            vec3(0.0, 0.0, 1.0),
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+        ),
+        (
+            vec3(0.0, 0.0, -1.0),
+            vec3(-1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+        ),
+    ];
+    let face_colors = [
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+        [1.0, 1.0, 0.0],
+    ];
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for (f, (norm, u, v)) in faces.iter().enumerate() {
+        let col = if color_cube { face_colors[f] } else { color };
+        let start = vertices.len() as u32;
+
+        for (a, b) in corners {
+            let pos = *norm + *u * a + *v * b;
+            let tex = [(a + 1.0) * 0.5, (b + 1.0) * 0.5];
+            vertices.push(vertex(pos, *norm, tex, col));
+        }
+
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+
+    into_mesh(vertices, indices, color)
+}
+
+/// ring of radius 1 - tube radius around the y axis, so the outer edge touches the unit circle
+/// ring_divs goes around the y axis and tube_divs around the tube itself
+pub fn torus(ring_divs: u32, tube_divs: u32, tube_radius: f32, color: [f32; 3]) -> Mesh {
+    let ring_divs = ring_divs.max(3);
+    let tube_divs = tube_divs.max(3);
+    let ring_radius = 1.0 - tube_radius;
+
+    let mut vertices = Vec::new();
+    for i in 0..=ring_divs {
+        let u = 2.0 * PIE * i as f32 / ring_divs as f32;
+        let center = vec3(f32::cos(u), 0.0, f32::sin(u)) * ring_radius;
+        for j in 0..=tube_divs {
+            let v = 2.0 * PIE * j as f32 / tube_divs as f32;
+            let norm = vec3(
+                f32::cos(v) * f32::cos(u),
+                f32::sin(v),
+                f32::cos(v) * f32::sin(u),
+            );
+            let tex = [i as f32 / ring_divs as f32, j as f32 / tube_divs as f32];
+
+            vertices.push(vertex(center + norm * tube_radius, norm, tex, color));
+        }
+    }
+
+    let mut indices = Vec::new();
+    grid_indices(ring_divs, tube_divs, 0, &mut indices);
+
+    into_mesh(vertices, indices, color)
+}
+
+/// flat square on the xz plane facing up
+pub fn plane(divs: u32, color: [f32; 3]) -> Mesh {
+    let divs = divs.max(1);
+    let norm = vec3(0.0, 1.0, 0.0);
+
+    let mut vertices = Vec::new();
+    for i in 0..=divs {
+        let t = i as f32 / divs as f32;
+        for j in 0..=divs {
+            let s = j as f32 / divs as f32;
+            let pos = vec3(-1.0 + 2.0 * s, 0.0, 1.0 - 2.0 * t);
+
+            vertices.push(vertex(pos, norm, [s, t], color));
+        }
+    }
+
+    let mut indices = Vec::new();
+    grid_indices(divs, divs, 0, &mut indices);
+
+    into_mesh(vertices, indices, color)
+}
+
+/// capped cylinder along the y axis
+pub fn cylinder(divs: u32, color: [f32; 3]) -> Mesh {
+    let divs = divs.max(3);
+
+    let mut vertices = Vec::new();
+    for (i, y) in [1.0, -1.0].iter().enumerate() {
+        for j in 0..=divs {
+            let phi = 2.0 * PIE * j as f32 / divs as f32;
+            let norm = vec3(f32::cos(phi), 0.0, f32::sin(phi));
+            let tex = [j as f32 / divs as f32, 1.0 - i as f32];
+
+            vertices.push(vertex(norm + vec3(0.0, *y, 0.0), norm, tex, color));
+        }
+    }
+
+    let mut indices = Vec::new();
+    grid_indices(1, divs, 0, &mut indices);
+
+    cap(divs, 1.0, color, &mut vertices, &mut indices);
+    cap(divs, -1.0, color, &mut vertices, &mut indices);
+
+    into_mesh(vertices, indices, color)
+}
+
+/// cone along the y axis with its tip at the top
+pub fn cone(divs: u32, color: [f32; 3]) -> Mesh {
+    let divs = divs.max(3);
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    // side normals lean up by the slope, height 2 radius 1
+    let slope_normal = |phi: f32| vec3(2.0 * f32::cos(phi), 1.0, 2.0 * f32::sin(phi)).unit();
+
+    for j in 0..divs {
+        let phi = 2.0 * PIE * j as f32 / divs as f32;
+        let next = 2.0 * PIE * (j + 1) as f32 / divs as f32;
+        let s = j as f32 / divs as f32;
+        let s_next = (j + 1) as f32 / divs as f32;
+
+        let start = vertices.len() as u32;
+        let tip_normal = slope_normal((phi + next) * 0.5);
+        vertices.push(vertex(
+            vec3(0.0, 1.0, 0.0),
+            tip_normal,
+            [(s + s_next) * 0.5, 1.0],
+            color,
+        ));
+        vertices.push(vertex(
+            vec3(f32::cos(phi), -1.0, f32::sin(phi)),
+            slope_normal(phi),
+            [s, 0.0],
+            color,
+        ));
+        vertices.push(vertex(
+            vec3(f32::cos(next), -1.0, f32::sin(next)),
+            slope_normal(next),
+            [s_next, 0.0],
+            color,
+        ));
+
+        indices.extend([start, start + 2, start + 1]);
+    }
+
+    cap(divs, -1.0, color, &mut vertices, &mut indices);
+
+    into_mesh(vertices, indices, color)
+}
+
+/// pill shape along the y axis, two hemispheres joined by a cylinder
+/// half_height is how far the hemisphere centers sit from the origin
+/// so the whole thing is 2 * (1 + half_height) tall
+pub fn capsule(lats: u32, longs: u32, half_height: f32, color: [f32; 3]) -> Mesh {
+    // an even lats count so the equator lands on a ring
+    let lats = lats.max(2).div_ceil(2) * 2;
+    let longs = longs.max(3);
+    let total_height = 2.0 * (1.0 + half_height);
+
+    // the equator ring is emitted twice, once for each hemisphere
+    // and the band in between them is the cylinder
+    let mut rings = Vec::new();
+    for i in 0..=lats / 2 {
+        rings.push((PIE * i as f32 / lats as f32, half_height));
+    }
+    for i in lats / 2..=lats {
+        rings.push((PIE * i as f32 / lats as f32, -half_height));
+    }
+
+    let mut vertices = Vec::new();
+    for (theta, offset) in rings.iter() {
+        for j in 0..=longs {
+            let phi = 2.0 * PIE * j as f32 / longs as f32;
+            let norm = vec3(
+                f32::sin(*theta) * f32::cos(phi),
+                f32::cos(*theta),
+                f32::sin(*theta) * f32::sin(phi),
+            );
+            let pos = norm + vec3(0.0, *offset, 0.0);
+            let tex = [j as f32 / longs as f32, 0.5 + pos.y / total_height];
+
+            vertices.push(vertex(pos, norm, tex, color));
+        }
+    }
+
+    let mut indices = Vec::new();
+    grid_indices(rings.len() as u32 - 1, longs, 0, &mut indices);
+
+    into_mesh(vertices, indices, color)
+}
+
+//_______________________________________________________________________________________________
+// helpers
+
+fn vertex(pos: Vec3, norm: Vec3, tex: [f32; 2], col: [f32; 3]) -> Vertex {
+    Vertex {
+        pos: pos.to_array(),
+        norm: norm.to_array(),
+        tex,
+        col,
+        ..Vertex::DEFAULT
+    }
+}
+
+/// triangulate a (rows + 1) x (cols + 1) grid of vertices laid out row after row
+/// the shape has to go down the rows and around the columns
+/// so that cross(column direction, row direction) points outwards
+fn grid_indices(rows: u32, cols: u32, offset: u32, indices: &mut Vec<u32>) {
+    for i in 0..rows {
+        for j in 0..cols {
+            let a = offset + i * (cols + 1) + j;
+            let b = a + cols + 1;
+
+            indices.extend([a, a + 1, b, a + 1, b + 1, b]);
+        }
+    }
+}
+
+/// flat disk of radius 1 at height y, facing up if y is positive and down otherwise
+fn cap(divs: u32, y: f32, color: [f32; 3], vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>) {
+    let norm = vec3(0.0, y.signum(), 0.0);
+    let center = vertices.len() as u32;
+
+    vertices.push(vertex(vec3(0.0, y, 0.0), norm, [0.5, 0.5], color));
+    for j in 0..=divs {
+        let phi = 2.0 * PIE * j as f32 / divs as f32;
+        let (s, c) = (f32::sin(phi), f32::cos(phi));
+        let tex = [0.5 + c * 0.5, 0.5 + s * 0.5];
+
+        vertices.push(vertex(vec3(c, y, s), norm, tex, color));
+    }
+
+    for j in 0..divs {
+        let current = center + 1 + j;
+        if y > 0.0 {
+            indices.extend([center, current + 1, current]);
+        } else {
+            indices.extend([center, current, current + 1]);
+        }
+    }
+}
+
+fn into_mesh(vertices: Vec<Vertex>, indices: Vec<u32>, color: [f32; 3]) -> Mesh {
+    let mut mesh = Mesh::default();
+
+    mesh.vbo.data = vertices;
+    mesh.ebo = Some(EBO::default());
+    mesh.ebo.as_mut().unwrap().data = indices;
+    mesh.material = Materail::Phong(Phong {
+        base_color: color,
+        ..Phong::default()
+    });
+
+    mesh.create();
+
+    mesh
+}
@@ -136,10 +136,10 @@ impl Drop for Shader {
 }
 
 fn shader_from_src(path: &Path, kind: gl::types::GLenum) -> Result<gl::types::GLuint, String> {
-    let mut file = File::open(path).unwrap();
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
 
     let mut src = String::new();
-    file.read_to_string(&mut src).unwrap();
+    file.read_to_string(&mut src).map_err(|e| e.to_string())?;
 
     let src_as_cstr = CString::new(src).unwrap();
 
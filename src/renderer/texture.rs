@@ -2,6 +2,26 @@ use gl;
 use image;
 use std::os::raw::c_void;
 
+/// how a texture gets sampled, all values are gl enums
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sampling {
+    pub wrap_s: gl::types::GLenum,
+    pub wrap_t: gl::types::GLenum,
+    pub min_filter: gl::types::GLenum,
+    pub mag_filter: gl::types::GLenum,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self {
+            wrap_s: gl::REPEAT,
+            wrap_t: gl::REPEAT,
+            min_filter: gl::LINEAR,
+            mag_filter: gl::LINEAR,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Texture {
     pub id: u32,
@@ -13,55 +33,166 @@ impl Texture {
     }
 
     pub fn from(&mut self, path: &std::path::Path) -> Result<(), String> {
-        let img = image::open(path).unwrap().flipv();
+        let img = image::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
 
-        let format = match img.color() {
-            image::ColorType::Rgb8 => gl::RGB,
-            image::ColorType::Rgba8 => gl::RGBA,
+        match img.color() {
+            image::ColorType::Rgb8 => self.load_pixels(
+                img.width(),
+                img.height(),
+                gl::RGB,
+                gl::UNSIGNED_BYTE,
+                img.as_bytes(),
+            ),
+            image::ColorType::Rgba8 => self.load_pixels(
+                img.width(),
+                img.height(),
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                img.as_bytes(),
+            ),
 
+            // anything else(grayscale, 16 bit...) gets converted
             _ => {
-                return Err(String::from("image format not recognised!"));
+                let rgba = img.to_rgba8();
+                self.load_pixels(
+                    rgba.width(),
+                    rgba.height(),
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    rgba.as_raw(),
+                )
             }
-        };
+        }
+    }
 
-        let pixels_buffer_ptr = {
-            if format == gl::RGB {
-                let pixels = img.as_rgb8().unwrap();
-                pixels.as_ptr() as *mut c_void
-            } else {
-                let pixels = img.as_rgba8().unwrap();
-                pixels.as_ptr() as *mut c_void
-            }
+    /// upload already decoded pixels, rows go top to bottom like in an image file  
+    /// format is the gl pixel format(gl::RGB, gl::RGBA...) and data_type the channel type
+    pub fn load_pixels(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: gl::types::GLenum,
+        data_type: gl::types::GLenum,
+        pixels: &[u8],
+    ) -> Result<(), String> {
+        let channels = match format {
+            gl::RED => 1,
+            gl::RG => 2,
+            gl::RGB => 3,
+            gl::RGBA => 4,
+            _ => return Err(String::from("image format not recognised!")),
+        };
+        let channel_size = match data_type {
+            gl::UNSIGNED_BYTE => 1,
+            gl::UNSIGNED_SHORT => 2,
+            gl::FLOAT => 4,
+            _ => return Err(String::from("image data type not recognised!")),
         };
 
+        let row_size = (width * channels * channel_size) as usize;
+        if row_size == 0 || pixels.len() != row_size * height as usize {
+            return Err(format!(
+                "expected {}x{} pixels, got {} bytes",
+                width,
+                height,
+                pixels.len()
+            ));
+        }
+
+        // opengl wants the bottom row first
+        let flipped: Vec<u8> = pixels
+            .chunks_exact(row_size)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
         unsafe {
             gl::CreateTextures(gl::TEXTURE_2D, 1, &mut self.id);
 
             gl::BindTexture(gl::TEXTURE_2D, self.id);
 
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            // rows of rgb images aren't always 4 byte aligned
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
 
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
                 format as i32,
-                img.width() as i32,
-                img.height() as i32,
+                width as i32,
+                height as i32,
                 0,
                 format,
-                gl::UNSIGNED_BYTE,
-                pixels_buffer_ptr,
+                data_type,
+                flipped.as_ptr() as *const c_void,
             );
 
             gl::GenerateMipmap(gl::TEXTURE_2D);
         };
+        self.set_sampling(&Sampling::default());
 
         Ok(())
     }
 
+    /// mipmaps always get generated so any min filter works
+    pub fn set_sampling(&self, sampling: &Sampling) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_S, sampling.wrap_s as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_T, sampling.wrap_t as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_MIN_FILTER, sampling.min_filter as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_MAG_FILTER, sampling.mag_filter as i32);
+        }
+    }
+
+    /// the sampling last set on the texture
+    pub fn sampling(&self) -> Sampling {
+        let get = |param| {
+            let mut value = 0;
+            unsafe {
+                gl::GetTextureParameteriv(self.id, param, &mut value);
+            }
+            value as gl::types::GLenum
+        };
+
+        Sampling {
+            wrap_s: get(gl::TEXTURE_WRAP_S),
+            wrap_t: get(gl::TEXTURE_WRAP_T),
+            min_filter: get(gl::TEXTURE_MIN_FILTER),
+            mag_filter: get(gl::TEXTURE_MAG_FILTER),
+        }
+    }
+
+    /// read the pixels back from the gpu as rgba, rows top to bottom like load_pixels takes them
+    pub fn read_pixels(&self) -> (u32, u32, Vec<u8>) {
+        let (mut width, mut height) = (0, 0);
+        unsafe {
+            gl::GetTextureLevelParameteriv(self.id, 0, gl::TEXTURE_WIDTH, &mut width);
+            gl::GetTextureLevelParameteriv(self.id, 0, gl::TEXTURE_HEIGHT, &mut height);
+        }
+
+        let row = width.max(0) as usize * 4;
+        if row == 0 || height <= 0 {
+            return (0, 0, Vec::new());
+        }
+        let mut data = vec![0u8; row * height.max(0) as usize];
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::GetTextureImage(
+                self.id,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.len() as i32,
+                data.as_mut_ptr() as *mut std::ffi::c_void,
+            );
+        }
+
+        // undo the flip from uploading
+        let flipped = data.chunks_exact(row).rev().flatten().copied().collect();
+
+        (width as u32, height as u32, flipped)
+    }
+
     pub fn bind(&self) {
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, self.id);
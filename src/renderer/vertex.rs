@@ -8,6 +8,8 @@ pub struct Vertex {
     pub pos: [f32; 3],
     pub norm: [f32; 3],
     pub tex: [f32; 2],
+    /// second uv set(lightmaps, detail textures...)
+    pub tex1: [f32; 2],
     pub col: [f32; 3],
 
     pub weights: [f32; 4],
@@ -18,6 +20,7 @@ impl Vertex {
         pos: [0.0; 3],
         norm: [0.0; 3],
         tex: [0.0; 2],
+        tex1: [0.0; 2],
         col: [0.0; 3],
 
         weights: [0.0; 4],
@@ -93,6 +96,18 @@ impl Vertex {
                 vert_size as i32,
                 offset_of!(Vertex, bone_ids) as *const c_void,
             );
+
+            // _________________________________________________
+            // _________________________________________________
+            gl::EnableVertexAttribArray(6);
+            gl::VertexAttribPointer(
+                6,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                vert_size as i32,
+                offset_of!(Vertex, tex1) as *const c_void,
+            );
         }
     }
 }
@@ -7,10 +7,22 @@ pub enum Direction {
     Left,
     Right,
 }
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    /// ymag is half the height of the view volume, the width follows the window
+    Orthographic {
+        ymag: f32,
+    },
+}
+
 pub struct Camera {
     yaw: f32,
     pitch: f32,
     fov: f32,
+    pub projection: Projection,
+    pub near: f32,
+    pub far: f32,
     pub front: Vec3,
     pub up: Vec3,
     pub pos: Vec3,
@@ -27,6 +39,9 @@ impl Camera {
             pos: vec3(0.0, 4.0, 0.0),
             velocity: 0.5,
             fov: 45.0,
+            projection: Projection::Perspective,
+            near: 1e-2,
+            far: 1e3,
             pitch: 0.0,
             yaw: radians(90.0),
             sensitivity: 0.15,
@@ -44,12 +59,39 @@ impl Camera {
         }
     }
 
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+    }
+
+    /// point the camera along a direction  
+    /// yaw and pitch are recovered from it so mouse rotation carries on smoothly from there
+    pub fn look_towards(&mut self, dir: Vec3) {
+        // leave unit vectors alone so a saved camera loads back exactly
+        if (dir.len() - 1.0).abs() > 1e-4 {
+            self.front = dir.unit();
+        } else {
+            self.front = dir;
+        }
+        self.pitch = f32::asin(clamp(self.front.y, -1.0, 1.0));
+        self.yaw = f32::atan2(self.front.z, self.front.x);
+    }
+
     pub fn get_view(&self) -> Mat4 {
         look_at(&self.pos, &(self.pos + self.front), &self.up)
     }
 
     pub fn get_pojection(&self, ratio: f32) -> Mat4 {
-        perspective(self.fov, ratio, 1e-2, 1e3)
+        match self.projection {
+            Projection::Perspective => perspective(self.fov, ratio, self.near, self.far),
+            Projection::Orthographic { ymag } => {
+                let xmag = ymag * ratio;
+                orthogonal(-xmag, xmag, ymag, -ymag, self.near, self.far)
+            }
+        }
     }
 
     pub fn rotate(&mut self, mouse_pos_x: i32, mouse_pos_y: i32) {
@@ -0,0 +1,452 @@
+use std::path::Path;
+
+use json::{object, JsonValue};
+
+use super::camera::{Camera, Projection};
+use super::lights::*;
+use super::shapes::{Shape, ShapeKind};
+use crate::src::math::{quaternion::*, transform::Transform, vec3::*};
+use crate::src::renderer::material::{Pattern, PatternKind, PatternSpace};
+use crate::src::renderer::shaders::{Program, Shader};
+use crate::src::renderer::shadows;
+
+// helpers for reading the scene description (world.json)
+// every error message carries the path of the offending field
+// so whoever edits the file knows exactly what to fix
+
+/// get a field from an object, erroring out if its not there
+pub fn field<'a>(value: &'a JsonValue, ctx: &str, key: &str) -> Result<&'a JsonValue, String> {
+    if !value.is_object() {
+        return Err(format!("`{ctx}` should be an object"));
+    }
+    if !value.has_key(key) {
+        return Err(format!("missing field `{}`", join(ctx, key)));
+    }
+
+    Ok(&value[key])
+}
+
+pub fn read_f32(value: &JsonValue, ctx: &str, key: &str) -> Result<f32, String> {
+    field(value, ctx, key)?
+        .as_f32()
+        .ok_or(format!("`{}` should be a number", join(ctx, key)))
+}
+
+pub fn read_usize(value: &JsonValue, ctx: &str, key: &str) -> Result<usize, String> {
+    field(value, ctx, key)?
+        .as_usize()
+        .ok_or(format!("`{}` should be a positive integer", join(ctx, key)))
+}
+
+pub fn read_bool(value: &JsonValue, ctx: &str, key: &str) -> Result<bool, String> {
+    field(value, ctx, key)?
+        .as_bool()
+        .ok_or(format!("`{}` should be true or false", join(ctx, key)))
+}
+
+pub fn read_str<'a>(value: &'a JsonValue, ctx: &str, key: &str) -> Result<&'a str, String> {
+    field(value, ctx, key)?
+        .as_str()
+        .ok_or(format!("`{}` should be a string", join(ctx, key)))
+}
+
+/// read a fixed size array of numbers e.g. [1.0, 0.5, 0.0]
+pub fn read_array<const N: usize>(
+    value: &JsonValue,
+    ctx: &str,
+    key: &str,
+) -> Result<[f32; N], String> {
+    let array = field(value, ctx, key)?;
+    let error = format!("`{}` should be an array of {N} numbers", join(ctx, key));
+
+    if !array.is_array() || array.len() != N {
+        return Err(error);
+    }
+
+    let mut result = [0.0; N];
+    for (i, member) in array.members().enumerate() {
+        result[i] = member.as_f32().ok_or(error.clone())?;
+    }
+
+    Ok(result)
+}
+
+pub fn read_vec3(value: &JsonValue, ctx: &str, key: &str) -> Result<Vec3, String> {
+    Ok(Vec3::from(&read_array::<3>(value, ctx, key)?))
+}
+
+/// iterate over an array field, handing out the context for each member
+pub fn read_list<'a>(
+    value: &'a JsonValue,
+    ctx: &str,
+    key: &str,
+) -> Result<Vec<(String, &'a JsonValue)>, String> {
+    let list = field(value, ctx, key)?;
+    if !list.is_array() {
+        return Err(format!("`{}` should be an array", join(ctx, key)));
+    }
+
+    Ok(list
+        .members()
+        .enumerate()
+        .map(|(i, member)| (format!("{}[{i}]", join(ctx, key)), member))
+        .collect())
+}
+
+fn join(ctx: &str, key: &str) -> String {
+    if ctx.is_empty() {
+        key.to_string()
+    } else {
+        format!("{ctx}.{key}")
+    }
+}
+
+//_______________________________________________________________________________________________
+// scene objects
+
+pub fn read_camera(value: &JsonValue, ctx: &str) -> Result<Camera, String> {
+    let mut camera = Camera::default();
+
+    camera.pos = read_vec3(value, ctx, "pos")?;
+    camera.look_towards(read_vec3(value, ctx, "front")?);
+    camera.set_fov(read_f32(value, ctx, "fov")?);
+
+    // clip planes and orthographic projection are optional
+    if value.has_key("near") {
+        camera.near = read_f32(value, ctx, "near")?;
+    }
+    if value.has_key("far") {
+        camera.far = read_f32(value, ctx, "far")?;
+    }
+    if value.has_key("orthographic") {
+        camera.projection = Projection::Orthographic {
+            ymag: read_f32(value, ctx, "orthographic")?,
+        };
+    }
+
+    Ok(camera)
+}
+
+/// position, scale and an optional orientation quaternion [x, y, z, s]
+pub fn read_transform(value: &JsonValue, ctx: &str) -> Result<Transform, String> {
+    let mut transform = Transform::DEFAULT;
+
+    transform.translation = read_vec3(value, ctx, "pos")?;
+    transform.scaling = read_vec3(value, ctx, "scale")?;
+    transform.orientation = read_orientation(value, ctx)?;
+
+    Ok(transform)
+}
+
+/// optional orientation quaternion [x, y, z, s], no rotation if its left out
+fn read_orientation(value: &JsonValue, ctx: &str) -> Result<Quat, String> {
+    if !value.has_key("orientation") {
+        return Ok(Quat::ZERO);
+    }
+
+    let orientation = Quat::from(&read_array::<4>(value, ctx, "orientation")?);
+    // only touch it if its noticeably off, saved files should load back bit for bit
+    if (orientation.norm() - 1.0).abs() > 1e-4 {
+        Ok(orientation.unit())
+    } else {
+        Ok(orientation)
+    }
+}
+
+pub fn read_shape(value: &JsonValue, ctx: &str) -> Result<Shape, String> {
+    let name = read_str(value, ctx, "name")?;
+    let color = read_array::<3>(value, ctx, "color")?;
+
+    let kind = match read_str(value, ctx, "type")? {
+        "sphere" => ShapeKind::Sphere {
+            lats: read_usize(value, ctx, "lats")? as u32,
+            longs: read_usize(value, ctx, "longs")? as u32,
+        },
+        "cube" => ShapeKind::Cube {
+            color_cube: read_bool(value, ctx, "colorCube")?,
+        },
+        "torus" => ShapeKind::Torus {
+            divs: read_usize(value, ctx, "divs")? as u32,
+            tube_radius: if value.has_key("tubeRadius") {
+                read_f32(value, ctx, "tubeRadius")?
+            } else {
+                0.3
+            },
+        },
+        "plane" => ShapeKind::Plane {
+            divs: read_usize(value, ctx, "divs")? as u32,
+        },
+        "cylinder" => ShapeKind::Cylinder {
+            divs: read_usize(value, ctx, "divs")? as u32,
+        },
+        "cone" => ShapeKind::Cone {
+            divs: read_usize(value, ctx, "divs")? as u32,
+        },
+        "capsule" => ShapeKind::Capsule {
+            lats: read_usize(value, ctx, "lats")? as u32,
+            longs: read_usize(value, ctx, "longs")? as u32,
+            half_height: read_f32(value, ctx, "halfHeight")?,
+        },
+        other => return Err(format!("`{ctx}.type`: unknown shape \"{other}\"")),
+    };
+
+    let mut shape = Shape::new(name, kind, color);
+
+    shape.model.transform.translation = read_vec3(value, ctx, "position")?;
+    shape.model.transform.scaling = read_vec3(value, ctx, "scale")?;
+    shape.model.transform.orientation = read_orientation(value, ctx)?;
+
+    if value.has_key("pattern") {
+        let pattern = read_pattern(&value["pattern"], &join(ctx, "pattern"), color)?;
+        shape.set_pattern(pattern);
+    }
+
+    Ok(shape)
+}
+
+/// reads either the explicit form
+/// {"type": "checkered", "color": [..], "scale": 20, "space": "object"}
+/// or the older packed form where the second color is a shade of the base color
+/// checkered: "values": [shade, scale], striped: "values": [shade, width, scale]
+pub fn read_pattern(value: &JsonValue, ctx: &str, base_color: [f32; 3]) -> Result<Pattern, String> {
+    let mut pattern = Pattern::default();
+
+    pattern.kind = match read_str(value, ctx, "type")? {
+        "none" => return Ok(pattern),
+        "checkered" => PatternKind::Checkered,
+        "striped" => PatternKind::Striped,
+        other => return Err(format!("`{ctx}.type`: unknown pattern \"{other}\"")),
+    };
+
+    if value.has_key("space") {
+        pattern.space = match read_str(value, ctx, "space")? {
+            "object" => PatternSpace::Object,
+            "uv" => PatternSpace::Uv,
+            other => {
+                return Err(format!(
+                    "`{ctx}.space`: expected \"object\" or \"uv\", got \"{other}\""
+                ))
+            }
+        };
+    }
+
+    if value.has_key("values") {
+        let values = match pattern.kind {
+            PatternKind::Striped => read_array::<3>(value, ctx, "values")?.to_vec(),
+            _ => read_array::<2>(value, ctx, "values")?.to_vec(),
+        };
+
+        pattern.color = base_color.map(|c| c * values[0]);
+        pattern.scale = *values.last().unwrap();
+        if pattern.kind == PatternKind::Striped {
+            pattern.width = values[1];
+        }
+    } else {
+        pattern.color = read_array::<3>(value, ctx, "color")?;
+        pattern.scale = read_f32(value, ctx, "scale")?;
+        if pattern.kind == PatternKind::Striped {
+            pattern.width = read_f32(value, ctx, "width")?;
+        }
+    }
+
+    Ok(pattern)
+}
+
+pub fn read_point_light(value: &JsonValue, ctx: &str) -> Result<PointLight, String> {
+    Ok(PointLight {
+        pos: read_vec3(value, ctx, "pos")?,
+        col: read_vec3(value, ctx, "col")?,
+    })
+}
+
+pub fn read_spot_light(value: &JsonValue, ctx: &str) -> Result<SpotLight, String> {
+    Ok(SpotLight {
+        pos: read_vec3(value, ctx, "pos")?,
+        dir: read_vec3(value, ctx, "dir")?,
+        col: read_vec3(value, ctx, "col")?,
+        inner_cone: read_f32(value, ctx, "innerCone")?,
+        outer_cone: read_f32(value, ctx, "outerCone")?,
+    })
+}
+
+pub fn read_sun(value: &JsonValue, ctx: &str) -> Result<DirectionalLight, String> {
+    Ok(DirectionalLight {
+        shadows: shadows::Shadow::new(1900, 1200),
+        dir: read_vec3(value, ctx, "dir")?,
+        color: read_vec3(value, ctx, "color")?,
+    })
+}
+
+/// where a program was built from, kept around so the scene can be saved again
+#[derive(Clone, Debug)]
+pub struct ProgramSource {
+    pub name: String,
+    pub vert: String,
+    pub frag: String,
+}
+
+/// compile the program described by a shader entry
+pub fn read_program(value: &JsonValue, ctx: &str) -> Result<(ProgramSource, Program), String> {
+    let source = ProgramSource {
+        name: read_str(value, ctx, "name")?.to_string(),
+        vert: read_str(value, ctx, "vert")?.to_string(),
+        frag: read_str(value, ctx, "frag")?.to_string(),
+    };
+
+    let vert = Path::new(&source.vert);
+    let frag = Path::new(&source.frag);
+
+    let program = Program::from_shaders(&[
+        Shader::from_vert_src(vert).map_err(|e| format!("{}: {e}", vert.display()))?,
+        Shader::from_frag_src(frag).map_err(|e| format!("{}: {e}", frag.display()))?,
+    ])
+    .map_err(|e| format!("`{ctx}` ({}) failed to link: {e}", source.name))?;
+
+    Ok((source, program))
+}
+
+//_______________________________________________________________________________________________
+// writing the scene back out
+// mirrors the read functions above so a saved scene loads back the same
+
+/// json's own f32 conversion widens the float first and ends up writing 0.7 as 0.699999988...
+/// going through the shortest string representation keeps the file readable and exact
+pub fn number(v: f32) -> JsonValue {
+    JsonValue::from(v.to_string().parse::<f64>().unwrap_or(0.0))
+}
+
+pub fn write_array(values: &[f32]) -> JsonValue {
+    JsonValue::from(values.iter().map(|v| number(*v)).collect::<Vec<_>>())
+}
+
+pub fn write_vec3(v: &Vec3) -> JsonValue {
+    write_array(&v.to_array())
+}
+
+pub fn write_camera(camera: &Camera) -> JsonValue {
+    let mut out = object! {
+        "fov": number(camera.fov()),
+        "front": write_vec3(&camera.front),
+        "pos": write_vec3(&camera.pos),
+        "near": number(camera.near),
+        "far": number(camera.far),
+    };
+    if let Projection::Orthographic { ymag } = camera.projection {
+        out["orthographic"] = number(ymag);
+    }
+    out
+}
+
+pub fn write_transform(transform: &Transform, out: &mut JsonValue) {
+    out["pos"] = write_vec3(&transform.translation);
+    out["scale"] = write_vec3(&transform.scaling);
+    out["orientation"] = write_array(&transform.orientation.to_array());
+}
+
+pub fn write_shape(shape: &Shape) -> JsonValue {
+    let mut out = object! { "name": shape.name.as_str() };
+
+    match shape.kind {
+        ShapeKind::Sphere { lats, longs } => {
+            out["type"] = "sphere".into();
+            out["lats"] = lats.into();
+            out["longs"] = longs.into();
+        }
+        ShapeKind::Cube { color_cube } => {
+            out["type"] = "cube".into();
+            out["colorCube"] = color_cube.into();
+        }
+        ShapeKind::Torus { divs, tube_radius } => {
+            out["type"] = "torus".into();
+            out["divs"] = divs.into();
+            out["tubeRadius"] = number(tube_radius);
+        }
+        ShapeKind::Plane { divs } => {
+            out["type"] = "plane".into();
+            out["divs"] = divs.into();
+        }
+        ShapeKind::Cylinder { divs } => {
+            out["type"] = "cylinder".into();
+            out["divs"] = divs.into();
+        }
+        ShapeKind::Cone { divs } => {
+            out["type"] = "cone".into();
+            out["divs"] = divs.into();
+        }
+        ShapeKind::Capsule {
+            lats,
+            longs,
+            half_height,
+        } => {
+            out["type"] = "capsule".into();
+            out["lats"] = lats.into();
+            out["longs"] = longs.into();
+            out["halfHeight"] = number(half_height);
+        }
+    }
+
+    let transform = &shape.model.transform;
+    out["scale"] = write_vec3(&transform.scaling);
+    out["position"] = write_vec3(&transform.translation);
+    out["orientation"] = write_array(&transform.orientation.to_array());
+    out["color"] = write_array(&shape.color);
+    out["pattern"] = write_pattern(&shape.pattern());
+
+    out
+}
+
+pub fn write_pattern(pattern: &Pattern) -> JsonValue {
+    let kind = match pattern.kind {
+        PatternKind::None => return object! { "type": "none" },
+        PatternKind::Checkered => "checkered",
+        PatternKind::Striped => "striped",
+    };
+    let space = match pattern.space {
+        PatternSpace::Object => "object",
+        PatternSpace::Uv => "uv",
+    };
+
+    let mut out = object! {
+        "type": kind,
+        "space": space,
+        "color": write_array(&pattern.color),
+        "scale": number(pattern.scale),
+    };
+    if pattern.kind == PatternKind::Striped {
+        out["width"] = number(pattern.width);
+    }
+
+    out
+}
+
+pub fn write_point_light(light: &PointLight) -> JsonValue {
+    object! {
+        "pos": write_vec3(&light.pos),
+        "col": write_vec3(&light.col),
+    }
+}
+
+pub fn write_spot_light(light: &SpotLight) -> JsonValue {
+    object! {
+        "pos": write_vec3(&light.pos),
+        "dir": write_vec3(&light.dir),
+        "col": write_vec3(&light.col),
+        "innerCone": number(light.inner_cone),
+        "outerCone": number(light.outer_cone),
+    }
+}
+
+pub fn write_sun(sun: &DirectionalLight) -> JsonValue {
+    object! {
+        "dir": write_vec3(&sun.dir),
+        "color": write_vec3(&sun.color),
+    }
+}
+
+pub fn write_program(source: &ProgramSource) -> JsonValue {
+    object! {
+        "name": source.name.as_str(),
+        "frag": source.frag.as_str(),
+        "vert": source.vert.as_str(),
+    }
+}
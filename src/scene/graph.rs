@@ -0,0 +1,190 @@
+use crate::src::math::transform::Transform;
+
+// hierarchical scene graph
+// nodes live in a flat list and point at each other by index, same idea as Pose.parents
+// only the root node has no parent
+
+pub type NodeId = usize;
+
+/// what a node drives once its world transform is known
+#[derive(Clone, Debug, PartialEq)]
+pub enum Attachment {
+    None,
+    /// model id in the world
+    Model(String),
+    /// index into the worlds point lights
+    PointLight(usize),
+    /// index into the worlds shapes
+    Shape(usize),
+    Camera,
+}
+
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: String,
+    pub local: Transform,
+    pub attachment: Attachment,
+    /// follow a joint of the model attached to the parent node(e.g. a helmet on the head joint)
+    pub joint: Option<usize>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    world: Transform,
+}
+
+impl Node {
+    fn new(name: &str, local: Transform, parent: Option<NodeId>) -> Self {
+        Self {
+            name: name.to_string(),
+            local,
+            attachment: Attachment::None,
+            joint: None,
+            parent,
+            children: Vec::new(),
+            world: local,
+        }
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// transform as of the last update
+    pub fn world(&self) -> Transform {
+        self.world
+    }
+}
+
+pub struct SceneGraph {
+    // removed nodes leave a hole so ids handed out stay valid
+    nodes: Vec<Option<Node>>,
+}
+
+impl SceneGraph {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Some(Node::new("root", Transform::DEFAULT, None))],
+        }
+    }
+
+    pub fn add(&mut self, name: &str, parent: NodeId, local: Transform) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Some(Node::new(name, local, Some(parent))));
+        self.node_mut(parent).children.push(id);
+
+        id
+    }
+
+    /// removes the node along with everything under it
+    pub fn remove(&mut self, id: NodeId) {
+        if id == Self::ROOT {
+            return;
+        }
+
+        if let Some(parent) = self.node(id).parent {
+            self.node_mut(parent).children.retain(|child| *child != id);
+        }
+
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            if let Some(node) = self.nodes[current].take() {
+                stack.extend(node.children);
+            }
+        }
+    }
+
+    /// move a node(and its children) under another parent, keeping its local transform
+    /// fails if the new parent sits somewhere under the node
+    pub fn reparent(&mut self, id: NodeId, parent: NodeId) -> Result<(), String> {
+        let mut current = Some(parent);
+        while let Some(ancestor) = current {
+            if ancestor == id {
+                return Err(format!(
+                    "can't move \"{}\" under its own child \"{}\"",
+                    self.node(id).name,
+                    self.node(parent).name
+                ));
+            }
+            current = self.node(ancestor).parent;
+        }
+
+        if let Some(old) = self.node(id).parent {
+            self.node_mut(old).children.retain(|child| *child != id);
+        }
+        self.node_mut(parent).children.push(id);
+        self.node_mut(id).parent = Some(parent);
+
+        Ok(())
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id).and_then(|node| node.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id).and_then(|node| node.as_mut())
+    }
+
+    /// panics if the node was removed
+    pub fn node(&self, id: NodeId) -> &Node {
+        self.get(id).unwrap()
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        self.get_mut(id).unwrap()
+    }
+
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.as_ref().is_some_and(|n| n.name == name))
+    }
+
+    /// every live node in an order where parents always come before their children
+    pub fn traversal(&self) -> Vec<NodeId> {
+        let mut order = vec![Self::ROOT];
+        let mut i = 0;
+        while i < order.len() {
+            order.extend_from_slice(&self.node(order[i]).children);
+            i += 1;
+        }
+
+        order
+    }
+
+    /// recompute world transforms from the local ones
+    /// joint_transform gives the model space transform of a joint of the model attached to a node,
+    /// it's asked for the parent of every node that follows a joint
+    pub fn update<F>(&mut self, mut joint_transform: F)
+    where
+        F: FnMut(&Attachment, usize) -> Option<Transform>,
+    {
+        for id in self.traversal() {
+            let node = self.node(id);
+
+            let parent_world = match node.parent {
+                None => Transform::DEFAULT,
+                Some(parent) => {
+                    let parent = self.node(parent);
+                    // the parent node places the model so the joint goes on top of it,
+                    // falls back to the parent itself if theres no such joint
+                    match node
+                        .joint
+                        .and_then(|j| joint_transform(&parent.attachment, j))
+                    {
+                        Some(joint) => Transform::combine(&parent.world, &joint),
+                        None => parent.world,
+                    }
+                }
+            };
+
+            let world = Transform::combine(&parent_world, &node.local);
+            self.node_mut(id).world = world;
+        }
+    }
+}
@@ -1,13 +1,30 @@
 extern crate gl;
-use crate::src::math::{mat4::*, vec3::*};
+use crate::src::math::{mat4::*, misc::radians, vec3::*};
 use crate::src::renderer::shaders;
 use crate::src::renderer::shadows;
 
+/// light array sizes in the lit shaders(MAX_POINT_LIGHTS/MAX_SPOT_LIGHTS), keep them in sync
+pub const MAX_POINT_LIGHTS: usize = 20;
+pub const MAX_SPOT_LIGHTS: usize = 8;
+
 #[derive(Clone, Copy)]
 pub struct PointLight {
     pub pos: Vec3,
     pub col: Vec3,
 }
+/// cone shaped light, angles in degrees from the center of the cone like the cameras fov  
+/// only pbr materials get lit by them, phong just has the sun
+#[derive(Clone, Copy)]
+pub struct SpotLight {
+    pub pos: Vec3,
+    pub dir: Vec3,
+    pub col: Vec3,
+    /// full intensity inside this angle
+    pub inner_cone: f32,
+    /// fades out to nothing at this one
+    pub outer_cone: f32,
+}
+
 // only directional light shadow support at the moment
 // might add point light shadows in the future who knows ¯\_(ツ)_/¯
 pub struct DirectionalLight {
@@ -44,3 +61,19 @@ pub fn pl_to_shader(light: PointLight, shader: &mut shaders::Program, i: usize)
     shader.update_vec3(pos.as_str(), light.pos);
     shader.update_vec3(col.as_str(), light.col);
 }
+
+/// send spot light to shaders spot light array
+pub fn sl_to_shader(light: SpotLight, shader: &mut shaders::Program, i: usize) {
+    let name = format!("spotLights[{i}]");
+    shader.update_vec3(format!("{name}.position").as_str(), light.pos);
+    shader.update_vec3(format!("{name}.direction").as_str(), light.dir.unit());
+    shader.update_vec3(format!("{name}.color").as_str(), light.col);
+    shader.update_float(
+        format!("{name}.innerCos").as_str(),
+        radians(light.inner_cone).cos(),
+    );
+    shader.update_float(
+        format!("{name}.outerCos").as_str(),
+        radians(light.outer_cone).cos(),
+    );
+}
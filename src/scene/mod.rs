@@ -1,3 +1,6 @@
 pub mod camera;
+pub mod file;
+pub mod graph;
 pub mod lights;
+pub mod shapes;
 pub mod viewer;
@@ -0,0 +1,80 @@
+use crate::src::renderer::{material::Pattern, model::Model, primitives};
+
+/// the primitive a shape is built from along with its resolution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShapeKind {
+    Sphere {
+        lats: u32,
+        longs: u32,
+    },
+    Cube {
+        color_cube: bool,
+    },
+    Torus {
+        divs: u32,
+        tube_radius: f32,
+    },
+    Plane {
+        divs: u32,
+    },
+    Cylinder {
+        divs: u32,
+    },
+    Cone {
+        divs: u32,
+    },
+    Capsule {
+        lats: u32,
+        longs: u32,
+        half_height: f32,
+    },
+}
+
+/// procedural object placed in the scene file
+/// keeps its description around so the scene can be saved again
+pub struct Shape {
+    pub name: String,
+    pub kind: ShapeKind,
+    pub color: [f32; 3],
+    pub model: Model,
+}
+
+impl Shape {
+    pub fn new(name: &str, kind: ShapeKind, color: [f32; 3]) -> Self {
+        let mesh = match kind {
+            ShapeKind::Sphere { lats, longs } => primitives::sphere(lats, longs, color),
+            ShapeKind::Cube { color_cube } => primitives::cube(color, color_cube),
+            ShapeKind::Torus { divs, tube_radius } => {
+                primitives::torus(divs, divs, tube_radius, color)
+            }
+            ShapeKind::Plane { divs } => primitives::plane(divs, color),
+            ShapeKind::Cylinder { divs } => primitives::cylinder(divs, color),
+            ShapeKind::Cone { divs } => primitives::cone(divs, color),
+            ShapeKind::Capsule {
+                lats,
+                longs,
+                half_height,
+            } => primitives::capsule(lats, longs, half_height, color),
+        };
+
+        let mut model = Model::default();
+        model.add_mesh(mesh);
+
+        Self {
+            name: name.to_string(),
+            kind,
+            color,
+            model,
+        }
+    }
+
+    pub fn pattern(&self) -> Pattern {
+        *self.model.meshes[0].material.pattern()
+    }
+
+    pub fn set_pattern(&mut self, pattern: Pattern) {
+        for mesh in self.model.meshes.iter_mut() {
+            *mesh.material.pattern_mut() = pattern;
+        }
+    }
+}
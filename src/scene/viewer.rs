@@ -1,167 +1,543 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
 use std::path::Path;
 
+use json::JsonValue;
+
 use super::camera::Camera;
+use super::file;
+use super::graph::{Attachment, SceneGraph};
 use super::lights::*;
+use super::shapes::Shape;
+use crate::src::animation::state_machine::StateMachine;
 use crate::src::foreign::*;
-
-use crate::src::renderer::{model::*, shaders, shadows};
+use crate::src::math::transform::Transform;
+
+use crate::src::renderer::{
+    material::{Pbr, Phong},
+    mesh::Mesh,
+    model::*,
+    shaders,
+};
 use shaders::Program;
 
 use crate::src::engine::timer::Timer;
-use crate::src::math::{quaternion::Quat, vec3::*};
+
+/// programs that get the camera and lights every frame
+/// along with whether they're for skinned models and whether they do pbr
+const LIT_PROGRAMS: [(&str, bool, bool); 4] = [
+    ("phong", false, false),
+    ("phongAnimation", true, false),
+    ("pbr", false, true),
+    ("pbrAnimated", true, true),
+];
 
 // abit messy but who cares
 // not sure why im bothering with comments as if anyone is going to read any of this
 pub struct World {
     pub camera: Camera,
-    pub player: Model,
+    /// loaded models keyed by id, kept sorted so saved scenes come out the same every time
+    models: BTreeMap<String, Model>,
+    pub shapes: Vec<Shape>,
+    /// parent/child transforms, nodes drive whatever is attached to them
+    pub graph: SceneGraph,
     pub sun: DirectionalLight,
     shaders: HashMap<String, Program>, //done
-    pub lights: Vec<PointLight>,       //done
+    programs: Vec<file::ProgramSource>,
+    pub lights: Vec<PointLight>, //done
+    pub spot_lights: Vec<SpotLight>,
 }
 
 impl World {
+    /// empty scene, no lights or programs
     pub fn new() -> Self {
-        let mut camera = Camera::default();
-        camera.pos = vec3(0.0, 20.0, -30.0);
-
-        let mut shaders = HashMap::new();
-        let lights = vec![
-            PointLight {
-                pos: vec3(30.0, 20.0, -20.0),
-                col: vec3(1.0, 1.0, 1.0),
-            },
-            PointLight {
-                pos: vec3(-30.0, 20.0, -20.0),
-                col: vec3(1.0, 0.6, 0.01),
-            },
-            PointLight {
-                pos: vec3(30.0, 20.0, 40.0),
-                col: vec3(1.0, 0.0, 1.0),
-            },
-            PointLight {
-                pos: vec3(-30.0, 20.0, 40.0),
-                col: vec3(0.0, 1.0, 0.5),
-            },
-        ];
-
-        let sun = DirectionalLight {
-            shadows: shadows::Shadow::new(1900, 1200),
-            color: vec3(1.0, 1.0, 1.0),
-            dir: vec3(0.3, -0.7, 0.4),
-        };
+        Self {
+            sun: DirectionalLight::default(),
+            camera: Camera::default(),
+            models: BTreeMap::new(),
+            shapes: Vec::new(),
+            graph: SceneGraph::new(),
+            shaders: HashMap::new(),
+            programs: Vec::new(),
+            lights: Vec::new(),
+            spot_lights: Vec::new(),
+        }
+    }
 
-        let file = gltf::Gltf::new(Path::new("models/astronaut"));
-        let mut player = Model::default();
-        file.populate_model(&mut player);
-        player.translate(vec3(0.0, 12.0, 3.0));
-        player.scale(vec3(0.5, 0.5, 0.5));
-        player.orient(Quat::create(180.0, vec3(0.0, 1.0, 0.0)));
+    /// build the scene described by a scene file(see world.json)
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let src = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let root = json::parse(&src).map_err(|e| format!("{}: {e}", path.display()))?;
 
-        player.play_animation = true;
-        player.current_anim = 0;
+        Self::from_json(&root).map_err(|e| format!("{}: {e}", path.display()))
+    }
 
-        let phong = shaders::create_shader(
-            &Path::new("shaders/common.vert"),
-            &Path::new("shaders/phong.frag"),
-        );
-        let phong_anim = shaders::create_shader(
-            &Path::new("shaders/animation.vert"),
-            &Path::new("shaders/phong.frag"),
-        );
+    fn from_json(root: &JsonValue) -> Result<Self, String> {
+        let mut world = Self::new();
 
-        let pbr = shaders::create_shader(
-            &Path::new("shaders/common.vert"),
-            &Path::new("shaders/pbr.frag"),
-        );
-        let pbr_anim = shaders::create_shader(
-            &Path::new("shaders/animation.vert"),
-            &Path::new("shaders/pbr.frag"),
-        );
+        world.camera = file::read_camera(file::field(root, "", "camera")?, "camera")?;
 
-        shaders.insert("phong".to_string(), phong);
-        shaders.insert("phongAnimation".to_string(), phong_anim);
-        shaders.insert("pbr".to_string(), pbr);
-        shaders.insert("pbrAnimation".to_string(), pbr_anim);
+        // the sun is optional, fall back to the default one
+        if root.has_key("sun") {
+            world.sun = file::read_sun(&root["sun"], "sun")?;
+        }
+
+        for (ctx, light) in file::read_list(root, "", "lights")? {
+            world.lights.push(file::read_point_light(light, &ctx)?);
+        }
+        if world.lights.len() > MAX_POINT_LIGHTS {
+            return Err(format!(
+                "`lights` has {} lights, the shaders take at most {MAX_POINT_LIGHTS}",
+                world.lights.len()
+            ));
+        }
+        if root.has_key("spotLights") {
+            for (ctx, light) in file::read_list(root, "", "spotLights")? {
+                world.spot_lights.push(file::read_spot_light(light, &ctx)?);
+            }
+        }
+        if world.spot_lights.len() > MAX_SPOT_LIGHTS {
+            return Err(format!(
+                "`spotLights` has {} lights, the shaders take at most {MAX_SPOT_LIGHTS}",
+                world.spot_lights.len()
+            ));
+        }
+
+        // procedural shapes are optional
+        if root.has_key("shapes") {
+            for (ctx, shape) in file::read_list(root, "", "shapes")? {
+                world.shapes.push(file::read_shape(shape, &ctx)?);
+            }
+        }
+
+        for (ctx, shader) in file::read_list(root, "", "shaders")? {
+            let (source, program) = file::read_program(shader, &ctx)?;
+            world.shaders.insert(source.name.clone(), program);
+            world.programs.push(source);
+        }
+        for name in ["phong", "phongAnimation"] {
+            if !world.shaders.contains_key(name) {
+                return Err(format!("`shaders` has no program named \"{name}\""));
+            }
+        }
 
         // prepare the textures in the shaders for rendering
-        {
-            let obj_shader = shaders.get_mut("phong").unwrap();
+        for shader in world.shaders.values_mut() {
+            shader.set_use();
+            shader.update_int("shadowMap", 0);
+            shader.update_int("albedo", 1);
+            shader.update_int("specular_tex", Phong::SPECULAR_UNIT as i32);
+            shader.update_int("baseTexture", Pbr::BASE_UNIT as i32);
+            shader.update_int("metallicTexture", Pbr::METALLIC_UNIT as i32);
+            shader.update_int("normalTexture", Pbr::NORMAL_UNIT as i32);
+            shader.update_int("occlusionTexture", Pbr::OCCLUSION_UNIT as i32);
+            shader.update_int("emissiveTexture", Pbr::EMISSIVE_UNIT as i32);
+        }
+
+        for (id, ctx, model) in Self::model_list(root)? {
+            world.read_model(id, model, &ctx)?;
+        }
 
-            obj_shader.set_use();
-            obj_shader.update_int("shadowMap", 0);
-            obj_shader.update_int("albedo", 1);
-            obj_shader.update_int("specular", 2);
+        // nodes come last since they refer to models, shapes and lights by name or index
+        if root.has_key("nodes") {
+            for (ctx, node) in file::read_list(root, "", "nodes")? {
+                world.read_node(node, &ctx)?;
+            }
         }
 
-        {
-            let anim_shader = shaders.get_mut("phongAnimation").unwrap();
+        Ok(world)
+    }
 
-            anim_shader.set_use();
-            anim_shader.update_int("shadowMap", 0);
-            anim_shader.update_int("albedo", 1);
-            anim_shader.update_int("specular", 2);
+    /// every model in the scene file as (id, context, value)  
+    /// older scene files only have a single "player" model
+    fn model_list(root: &JsonValue) -> Result<Vec<(&str, String, &JsonValue)>, String> {
+        let mut models = Vec::new();
+        if root.has_key("player") {
+            models.push(("player", String::from("player"), &root["player"]));
+        }
+        if root.has_key("models") {
+            for (ctx, model) in file::read_list(root, "", "models")? {
+                let id = file::read_str(model, &ctx, "id")?;
+                if models.iter().any(|(other, _, _)| *other == id) {
+                    return Err(format!("`{ctx}.id`: a model named \"{id}\" already exists"));
+                }
+                models.push((id, ctx, model));
+            }
         }
+        Ok(models)
+    }
 
-        Self {
-            sun,
-            camera,
-            player,
-            shaders,
-            lights,
+    /// write the current state of the scene in the same format `from_file` reads  
+    /// keys the world doesn't know about are kept from the file being overwritten
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut root = Self::saved_root(path);
+
+        root["camera"] = file::write_camera(&self.camera);
+        root["sun"] = file::write_sun(&self.sun);
+
+        root["models"] = self
+            .models
+            .iter()
+            .map(|(id, model)| Self::write_model(id, model))
+            .collect::<Vec<_>>()
+            .into();
+        root["shapes"] = self
+            .shapes
+            .iter()
+            .map(file::write_shape)
+            .collect::<Vec<_>>()
+            .into();
+        root["lights"] = self
+            .lights
+            .iter()
+            .map(file::write_point_light)
+            .collect::<Vec<_>>()
+            .into();
+        root["spotLights"] = self
+            .spot_lights
+            .iter()
+            .map(file::write_spot_light)
+            .collect::<Vec<_>>()
+            .into();
+        root["nodes"] = self.write_nodes().into();
+        root["shaders"] = self
+            .programs
+            .iter()
+            .map(file::write_program)
+            .collect::<Vec<_>>()
+            .into();
+
+        fs::write(path, json::stringify_pretty(root, 4))
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// the file being overwritten, minus the legacy "player" model since it gets saved in "models"
+    fn saved_root(path: &Path) -> JsonValue {
+        let mut root = fs::read_to_string(path)
+            .ok()
+            .and_then(|src| json::parse(&src).ok())
+            .filter(|root| root.is_object())
+            .unwrap_or_else(|| json::object! {});
+        root.remove("player");
+        root
+    }
+
+    fn write_model(id: &str, model: &Model) -> JsonValue {
+        let mut out = json::object! { "id": id, "file": model.file.as_str() };
+        file::write_transform(&model.transform, &mut out);
+        match &model.animator {
+            Some(animator) if !animator.file.is_empty() => {
+                out["stateMachine"] = animator.file.as_str().into();
+            }
+            Some(animator) => {
+                out["animation"] = match animator.current_clip(&model.animations) {
+                    Some((clip, _)) => clip.into(),
+                    None => JsonValue::Null,
+                };
+            }
+            None => out["animation"] = JsonValue::Null,
         }
+        out
     }
 
-    pub fn add_model(&mut self, id: String, path: &Path) {
-        let file = gltf::Gltf::new(path);
-        self.player = Model::default();
-        file.populate_model(&mut self.player);
-        self.player.translate(vec3(0.0, 12.0, 3.0));
-        self.player.scale(vec3(0.5, 0.5, 0.5));
-        self.player.orient(Quat::create(180.0, vec3(0.0, 1.0, 0.0)));
+    /// load a model from the scene file and place it
+    /// "animation" picks the clip to play, null stops it
+    /// and leaving it out plays the first clip if there is one  
+    /// "stateMachine" is a json file with a state machine for the models clips(see state_machine.rs)
+    /// and takes over from "animation"  
+    /// "importCamera" and "importLights" take over the camera and lights authored in the file,
+    /// they aren't saved back since the camera and lights get saved themselves
+    fn read_model(&mut self, id: &str, value: &JsonValue, ctx: &str) -> Result<(), String> {
+        let model_path = Path::new(file::read_str(value, ctx, "file")?);
+        let transform = file::read_transform(value, ctx)?;
+
+        let import_camera =
+            value.has_key("importCamera") && file::read_bool(value, ctx, "importCamera")?;
+        let import_lights =
+            value.has_key("importLights") && file::read_bool(value, ctx, "importLights")?;
+
+        Self::check_path(model_path).map_err(|e| format!("`{ctx}.file`: {e}"))?;
+        let model = if import_camera || import_lights {
+            // only glTF files carry cameras and lights
+            let gltf_file = gltf::Gltf::new(model_path)
+                .map_err(|e| format!("`{ctx}.file`: {}: {e}", model_path.display()))?;
+            if import_camera {
+                match gltf_file.extract_cameras(&transform).into_iter().next() {
+                    Some(camera) => self.camera = camera,
+                    None => return Err(format!("`{ctx}.importCamera`: the file has no cameras")),
+                }
+            }
+            if import_lights {
+                self.import_lights(&gltf_file, &transform);
+            }
+            Self::build_model(model_path, |model| gltf_file.populate_model(model))
+        } else {
+            Self::build_model(model_path, |model| load_model(model_path, model))
+        }
+        .map_err(|e| format!("`{ctx}.file`: {e}"))?;
+        let model = self.insert_model(id, model);
+        model.transform = transform;
+
+        if value.has_key("stateMachine") {
+            let path = Path::new(file::read_str(value, ctx, "stateMachine")?);
+            let machine = StateMachine::load(path, &model.animations)
+                .map_err(|e| format!("`{ctx}.stateMachine`: {e}"))?;
+            model.animator = Some(machine);
+        } else if value.has_key("animation") && value["animation"].is_null() {
+            model.stop();
+        } else if value.has_key("animation") {
+            let clip = file::read_usize(value, ctx, "animation")?;
+            if clip >= model.animations.len() {
+                return Err(format!(
+                    "`{ctx}.animation`: model only has {} animations",
+                    model.animations.len()
+                ));
+            }
+            model.play(clip);
+        }
 
-        self.player.play_animation = true;
-        self.player.current_anim = 0;
+        Ok(())
     }
 
-    pub fn update(&mut self, win_ratio: f32, timer: &Timer) {
-        // update camera movement
-        self.camera.update_motion();
-        // update animations for current model being viewed
-        self.player.update_animation(timer.elapsed);
+    /// nodes are listed parents first, "parent" is the name of an earlier node(the root if left out)
+    /// and "joint" makes the node follow a joint of the model attached to its parent
+    fn read_node(&mut self, value: &JsonValue, ctx: &str) -> Result<(), String> {
+        let name = file::read_str(value, ctx, "name")?;
+        if self.graph.find(name).is_some() {
+            return Err(format!(
+                "`{ctx}.name`: a node named \"{name}\" already exists"
+            ));
+        }
 
-        let lights = &self.lights;
-        //________________________________________________________________________
-        //update shader for static objects(no skeleton)
-        {
-            let shader = &mut self.shaders.get_mut("phong").unwrap();
+        let parent = if value.has_key("parent") {
+            let parent = file::read_str(value, ctx, "parent")?;
+            self.graph.find(parent).ok_or(format!(
+                "`{ctx}.parent`: no node named \"{parent}\" before this one"
+            ))?
+        } else {
+            SceneGraph::ROOT
+        };
 
-            shader.set_use();
-            self.sun.shadows.bind_texture();
-            shader.update_vec3("L_direction", self.sun.dir);
-            shader.update_vec3("L_color", self.sun.color);
-            shader.update_vec3("viewPos", self.camera.pos);
-            shader.update_mat4("view", &self.camera.get_view());
+        let attachment = if value.has_key("model") {
+            let id = file::read_str(value, ctx, "model")?;
+            if !self.models.contains_key(id) {
+                return Err(format!("`{ctx}.model`: no model with id \"{id}\""));
+            }
+            Attachment::Model(id.to_string())
+        } else if value.has_key("shape") {
+            let shape = file::read_str(value, ctx, "shape")?;
+            let index = self.shapes.iter().position(|s| s.name == shape);
+            Attachment::Shape(index.ok_or(format!("`{ctx}.shape`: no shape named \"{shape}\""))?)
+        } else if value.has_key("light") {
+            let index = file::read_usize(value, ctx, "light")?;
+            if index >= self.lights.len() {
+                return Err(format!("`{ctx}.light`: only {} lights", self.lights.len()));
+            }
+            Attachment::PointLight(index)
+        } else if value.has_key("camera") && file::read_bool(value, ctx, "camera")? {
+            Attachment::Camera
+        } else {
+            Attachment::None
+        };
 
-            shader.update_mat4("lightSpace", &self.sun.transform());
-            shader.update_int("shadowsEnabled", false as i32);
+        let joint = if value.has_key("joint") {
+            let joint = file::read_str(value, ctx, "joint")?;
+            let model = match &self.graph.node(parent).attachment {
+                Attachment::Model(id) => &self.models[id],
+                _ => return Err(format!("`{ctx}.joint`: parent node has no model attached")),
+            };
+            let index = model.joint_index(joint);
+            Some(index.ok_or(format!(
+                "`{ctx}.joint`: model has no joint named \"{joint}\""
+            ))?)
+        } else {
+            None
+        };
 
-            let projection = self.camera.get_pojection(win_ratio);
-            shader.update_mat4("projection", &projection);
+        let id = self
+            .graph
+            .add(name, parent, file::read_transform(value, ctx)?);
+        let node = self.graph.node_mut(id);
+        node.attachment = attachment;
+        node.joint = joint;
 
-            let len = lights.len();
-            shader.update_int("pointLightCount", len as i32);
+        Ok(())
+    }
 
-            for i in 0..len {
-                pl_to_shader(lights[i], shader, i);
+    fn write_nodes(&self) -> Vec<JsonValue> {
+        let mut nodes = Vec::new();
+
+        for id in self.graph.traversal().into_iter().skip(1) {
+            let node = self.graph.node(id);
+            let mut out = json::object! { "name": node.name.as_str() };
+
+            let parent = self.graph.node(node.parent().unwrap());
+            if node.parent() != Some(SceneGraph::ROOT) {
+                out["parent"] = parent.name.as_str().into();
+            }
+            file::write_transform(&node.local, &mut out);
+
+            match &node.attachment {
+                Attachment::None => {}
+                Attachment::Model(id) => out["model"] = id.as_str().into(),
+                Attachment::Shape(i) => out["shape"] = self.shapes[*i].name.as_str().into(),
+                Attachment::PointLight(i) => out["light"] = (*i).into(),
+                Attachment::Camera => out["camera"] = true.into(),
+            }
+
+            if let (Some(joint), Attachment::Model(model)) = (node.joint, &parent.attachment) {
+                out["joint"] = self.models[model].skeleton.joint_names[joint]
+                    .as_str()
+                    .into();
+            }
+
+            nodes.push(out);
+        }
+
+        nodes
+    }
+
+    /// push node world transforms onto whatever is attached to them
+    fn update_graph(&mut self) {
+        let models = &self.models;
+        self.graph.update(|attachment, joint| match attachment {
+            Attachment::Model(id) => models.get(id).and_then(|m| m.joint_transform(joint)),
+            _ => None,
+        });
+
+        for id in self.graph.traversal() {
+            let node = self.graph.node(id);
+            let world = node.world();
+
+            match &node.attachment {
+                Attachment::None => {}
+                Attachment::Model(id) => {
+                    if let Some(model) = self.models.get_mut(id) {
+                        model.transform = world;
+                    }
+                }
+                Attachment::Shape(i) => {
+                    if let Some(shape) = self.shapes.get_mut(*i) {
+                        shape.model.transform = world;
+                    }
+                }
+                Attachment::PointLight(i) => {
+                    if let Some(light) = self.lights.get_mut(*i) {
+                        light.pos = world.translation;
+                    }
+                }
+                Attachment::Camera => self.camera.pos = world.translation,
             }
         }
+    }
+
+    /// load a model(.gltf, .glb, .dae, .obj, .bvh or .dhm cache) and add it to the scene, replacing any model with the same id  
+    /// plays the first animation clip if the model has any
+    pub fn add_model(&mut self, id: &str, path: &Path) -> Result<&mut Model, String> {
+        Self::check_path(path)?;
+        let model = Self::build_model(path, |model| load_model(path, model))?;
+        Ok(self.insert_model(id, model))
+    }
+
+    /// replace the sun and lights with the ones authored in a glTF file, placed by the transform  
+    /// keeps the current sun if the file has no directional light
+    pub fn import_lights(&mut self, file: &gltf::Gltf, place: &Transform) {
+        let mut suns = Vec::new();
+        self.lights.clear();
+        self.spot_lights.clear();
+        file.extract_lights(place, &mut self.lights, &mut self.spot_lights, &mut suns);
+
+        // anything past what the shaders have room for gets dropped
+        if self.lights.len() > MAX_POINT_LIGHTS {
+            println!(
+                "only importing {MAX_POINT_LIGHTS} of {} point lights",
+                self.lights.len()
+            );
+            self.lights.truncate(MAX_POINT_LIGHTS);
+        }
+        if self.spot_lights.len() > MAX_SPOT_LIGHTS {
+            println!(
+                "only importing {MAX_SPOT_LIGHTS} of {} spot lights",
+                self.spot_lights.len()
+            );
+            self.spot_lights.truncate(MAX_SPOT_LIGHTS);
+        }
+
+        if let Some(sun) = suns.into_iter().next() {
+            self.sun = sun;
+        }
+    }
+
+    fn check_path(path: &Path) -> Result<(), String> {
+        // older scene files point at the folder the model sits in
+        if path.is_dir() {
+            return Err(format!(
+                "\"{}\" is a folder, point at the model file inside it",
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    fn build_model(
+        path: &Path,
+        populate: impl FnOnce(&mut Model) -> Result<(), error::LoadError>,
+    ) -> Result<Model, String> {
+        let mut model = Model::default();
+        populate(&mut model).map_err(|e| format!("{}: {e}", path.display()))?;
+        model.file = path.to_string_lossy().into_owned();
+
+        // first clip if there is one
+        model.play(0);
+
+        Ok(model)
+    }
+
+    /// add an already built model, replacing any model with the same id
+    pub fn insert_model(&mut self, id: &str, model: Model) -> &mut Model {
+        self.models.insert(id.to_string(), model);
+        self.models.get_mut(id).unwrap()
+    }
+
+    pub fn remove_model(&mut self, id: &str) -> Option<Model> {
+        self.models.remove(id)
+    }
+
+    pub fn model(&self, id: &str) -> Option<&Model> {
+        self.models.get(id)
+    }
+
+    pub fn model_mut(&mut self, id: &str) -> Option<&mut Model> {
+        self.models.get_mut(id)
+    }
+
+    /// every model along with its id
+    pub fn models(&self) -> impl Iterator<Item = (&String, &Model)> {
+        self.models.iter()
+    }
+
+    pub fn models_mut(&mut self) -> impl Iterator<Item = (&String, &mut Model)> {
+        self.models.iter_mut()
+    }
+
+    pub fn update(&mut self, win_ratio: f32, timer: &Timer) {
+        // update camera movement
+        self.camera.update_motion();
+        // every model keeps its own animation going
+        for model in self.models.values_mut() {
+            model.update_animation(timer.elapsed);
+        }
+        // after the animations so joints are in their new pose
+        self.update_graph();
+
+        let lights = &self.lights;
+        let projection = self.camera.get_pojection(win_ratio);
         //________________________________________________________________________
-        //update shader for dynamic objects(have a skeleton)
-        {
-            let shader = &mut self.shaders.get_mut("phongAnimation").unwrap();
-            let projection = self.camera.get_pojection(win_ratio);
+        //every lit program takes the same camera and light uniforms
+        for (name, ..) in LIT_PROGRAMS {
+            let Some(shader) = self.shaders.get_mut(name) else {
+                continue;
+            };
 
             shader.set_use();
             self.sun.shadows.bind_texture();
@@ -174,13 +550,19 @@ impl World {
             shader.update_mat4("lightSpace", &self.sun.transform());
             shader.update_int("shadowsEnabled", false as i32);
 
-            let len = lights.len();
-
+            // lights added in code can still go past the shaders arrays
+            let len = lights.len().min(MAX_POINT_LIGHTS);
             shader.update_int("pointLightCount", len as i32);
             // update point lights
             for i in 0..len {
                 pl_to_shader(lights[i], shader, i);
             }
+
+            let len = self.spot_lights.len().min(MAX_SPOT_LIGHTS);
+            shader.update_int("spotLightCount", len as i32);
+            for (i, light) in self.spot_lights.iter().take(len).enumerate() {
+                sl_to_shader(*light, shader, i);
+            }
         }
     }
 
@@ -194,9 +576,88 @@ impl World {
         self.player.render(shader);
         shadows::Shadow::detach(); */
 
-        //render model animated
-        let shader = &mut self.shaders.get_mut("phongAnimation").unwrap();
-        shader.set_use();
-        self.player.render(shader);
+        //pbr meshes go through the pbr programs when the scene has them
+        let pbr = self.shaders.contains_key("pbr");
+        let pbr_animated = self.shaders.contains_key("pbrAnimated");
+
+        //opaque surfaces first, see through ones get drawn on top of them
+        for blended in [false, true] {
+            for (name, skinned, pbr_program) in LIT_PROGRAMS {
+                let Some(shader) = self.shaders.get_mut(name) else {
+                    continue;
+                };
+                shader.set_use();
+
+                let pbr_available = if skinned { pbr_animated } else { pbr };
+
+                let filter = |mesh: &Mesh| {
+                    let use_pbr = mesh.material.is_pbr() && pbr_available;
+                    use_pbr == pbr_program && mesh.material.blended() == blended
+                };
+
+                //render static objects
+                if name == "phong" {
+                    for shape in self.shapes.iter_mut() {
+                        shape.model.render_filtered(shader, filter);
+                    }
+                }
+                //models with a skeleton need the animation shaders
+                for model in self.models.values_mut().filter(|m| m.skinned() == skinned) {
+                    model.render_filtered(shader, filter);
+                }
+            }
+        }
+
+        // materials leave culling and blending however the last one wanted them
+        unsafe {
+            gl::Disable(gl::CULL_FACE);
+            gl::Disable(gl::BLEND);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_player_survives_save_and_load() {
+        let path = std::env::temp_dir().join("doohickey_legacy_scene.json");
+        let legacy = json::object! {
+            "player": { "file": "resources/models/player.gltf" },
+            "notes": "kept",
+        };
+        fs::write(&path, json::stringify(legacy)).unwrap();
+
+        // what save writes for the player once it's been loaded
+        let mut root = World::saved_root(&path);
+        let mut player = Model::default();
+        player.file = String::from("resources/models/player.gltf");
+        root["models"] = vec![World::write_model("player", &player)].into();
+        fs::remove_file(&path).unwrap();
+
+        assert!(!root.has_key("player"));
+        assert_eq!(root["notes"], "kept");
+
+        let saved = json::parse(&json::stringify(root)).unwrap();
+        let models = World::model_list(&saved).unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].0, "player");
+        assert_eq!(
+            file::read_str(models[0].2, &models[0].1, "file"),
+            Ok("resources/models/player.gltf")
+        );
+    }
+
+    #[test]
+    fn duplicate_model_ids_are_rejected() {
+        let root = json::object! {
+            "player": { "file": "a.gltf" },
+            "models": [{ "id": "player", "file": "b.gltf" }],
+        };
+        assert_eq!(
+            World::model_list(&root).unwrap_err(),
+            "`models[0].id`: a model named \"player\" already exists"
+        );
     }
 }